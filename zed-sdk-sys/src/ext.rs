@@ -1,9 +1,82 @@
 use crate::{
     SL_InitParameters, SL_RuntimeParameters, SL_CAMERA_STATE, SL_COORDINATE_SYSTEM, SL_DEPTH_MODE,
-    SL_ERROR_CODE, SL_FLIP_MODE, SL_INPUT_TYPE, SL_MODEL,
-    SL_REFERENCE_FRAME_SL_REFERENCE_FRAME_CAMERA, SL_RESOLUTION, SL_UNIT,
+    SL_ERROR_CODE, SL_FLIP_MODE, SL_INPUT_TYPE, SL_MAT_TYPE, SL_MAT_TYPE_SL_MAT_TYPE_F32_C1,
+    SL_MAT_TYPE_SL_MAT_TYPE_F32_C4, SL_MAT_TYPE_SL_MAT_TYPE_U16_C1, SL_MAT_TYPE_SL_MAT_TYPE_U8_C1,
+    SL_MAT_TYPE_SL_MAT_TYPE_U8_C4, SL_MEASURE, SL_MODEL,
+    SL_REFERENCE_FRAME_SL_REFERENCE_FRAME_CAMERA, SL_RESOLUTION, SL_UNIT, SL_VIEW,
 };
-use std::fmt::{self, Display};
+use std::{
+    fmt::{self, Display},
+    os::raw::c_int,
+};
+
+/// Implements `TryFrom<c_int>` on a C enum. The unknown value is given back
+/// on error.
+macro_rules! impl_try_from_c_int {
+    ($ty:ident { $($variant:ident),* $(,)? }) => {
+        impl TryFrom<c_int> for $ty {
+            type Error = c_int;
+
+            fn try_from(value: c_int) -> Result<Self, Self::Error> {
+                $(
+                    if value == $ty::$variant as c_int {
+                        return Ok($ty::$variant);
+                    }
+                )*
+                Err(value)
+            }
+        }
+    };
+}
+
+impl_try_from_c_int!(SL_ERROR_CODE {
+    SL_ERROR_CODE_SUCCESS,
+    SL_ERROR_CODE_FAILURE,
+    SL_ERROR_CODE_NO_GPU_COMPATIBLE,
+    SL_ERROR_CODE_NOT_ENOUGH_GPU_MEMORY,
+    SL_ERROR_CODE_CAMERA_NOT_DETECTED,
+    SL_ERROR_CODE_SENSORS_NOT_INITIALIZED,
+    SL_ERROR_CODE_SENSORS_NOT_AVAILABLE,
+    SL_ERROR_CODE_INVALID_RESOLUTION,
+    SL_ERROR_CODE_LOW_USB_BANDWIDTH,
+    SL_ERROR_CODE_CALIBRATION_FILE_NOT_AVAILABLE,
+    SL_ERROR_CODE_INVALID_CALIBRATION_FILE,
+    SL_ERROR_CODE_INVALID_SVO_FILE,
+    SL_ERROR_CODE_SVO_RECORDING_ERROR,
+    SL_ERROR_CODE_SVO_UNSUPPORTED_COMPRESSION,
+    SL_ERROR_CODE_END_OF_SVOFILE_REACHED,
+    SL_ERROR_CODE_INVALID_COORDINATE_SYSTEM,
+    SL_ERROR_CODE_INVALID_FIRMWARE,
+    SL_ERROR_CODE_INVALID_FUNCTION_PARAMETERS,
+    SL_ERROR_CODE_CUDA_ERROR,
+    SL_ERROR_CODE_CAMERA_NOT_INITIALIZED,
+    SL_ERROR_CODE_NVIDIA_DRIVER_OUT_OF_DATE,
+    SL_ERROR_CODE_INVALID_FUNCTION_CALL,
+    SL_ERROR_CODE_CORRUPTED_SDK_INSTALLATION,
+    SL_ERROR_CODE_INCOMPATIBLE_SDK_VERSION,
+    SL_ERROR_CODE_INVALID_AREA_FILE,
+    SL_ERROR_CODE_INCOMPATIBLE_AREA_FILE,
+    SL_ERROR_CODE_CAMERA_FAILED_TO_SETUP,
+    SL_ERROR_CODE_CAMERA_DETECTION_ISSUE,
+    SL_ERROR_CODE_CANNOT_START_CAMERA_STREAM,
+    SL_ERROR_CODE_NO_GPU_DETECTED,
+    SL_ERROR_CODE_PLANE_NOT_FOUND,
+    SL_ERROR_CODE_MODULE_NOT_COMPATIBLE_WITH_CAMERA,
+    SL_ERROR_CODE_MOTION_SENSORS_REQUIRED,
+    SL_ERROR_CODE_MODULE_NOT_COMPATIBLE_WITH_CUDA_VERSION,
+});
+
+impl_try_from_c_int!(SL_CAMERA_STATE {
+    SL_CAMERA_STATE_AVAILABLE,
+    SL_CAMERA_STATE_NOT_AVAILABLE,
+});
+
+impl_try_from_c_int!(SL_MODEL {
+    SL_MODEL_ZED,
+    SL_MODEL_ZED_M,
+    SL_MODEL_ZED2,
+    SL_MODEL_ZED2i,
+});
 
 impl Default for SL_InitParameters {
     fn default() -> Self {
@@ -43,6 +116,94 @@ impl Default for SL_RuntimeParameters {
     }
 }
 
+impl SL_UNIT {
+    /// The length of the unit in meters.
+    pub fn in_meters(&self) -> f32 {
+        match self {
+            SL_UNIT::SL_UNIT_MILLIMETER => 0.001,
+            SL_UNIT::SL_UNIT_CENTIMETER => 0.01,
+            SL_UNIT::SL_UNIT_METER => 1.0,
+            SL_UNIT::SL_UNIT_INCH => 0.0254,
+            SL_UNIT::SL_UNIT_FOOT => 0.3048,
+        }
+    }
+}
+
+impl SL_VIEW {
+    /// The Mat type of the image of the view.
+    pub fn mat_type(&self) -> SL_MAT_TYPE {
+        match self {
+            SL_VIEW::SL_VIEW_LEFT_GRAY
+            | SL_VIEW::SL_VIEW_RIGHT_GRAY
+            | SL_VIEW::SL_VIEW_LEFT_UNRECTIFIED_GRAY
+            | SL_VIEW::SL_VIEW_RIGHT_UNRECTIFIED_GRAY => SL_MAT_TYPE_SL_MAT_TYPE_U8_C1,
+            SL_VIEW::SL_VIEW_LEFT
+            | SL_VIEW::SL_VIEW_RIGHT
+            | SL_VIEW::SL_VIEW_LEFT_UNRECTIFIED
+            | SL_VIEW::SL_VIEW_RIGHT_UNRECTIFIED
+            | SL_VIEW::SL_VIEW_SIDE_BY_SIDE
+            | SL_VIEW::SL_VIEW_DEPTH
+            | SL_VIEW::SL_VIEW_CONFIDENCE
+            | SL_VIEW::SL_VIEW_NORMALS
+            | SL_VIEW::SL_VIEW_DEPTH_RIGHT
+            | SL_VIEW::SL_VIEW_NORMALS_RIGHT => SL_MAT_TYPE_SL_MAT_TYPE_U8_C4,
+        }
+    }
+
+    /// The number of camera images placed side by side in the image of the
+    /// view.
+    pub fn width_factor(&self) -> usize {
+        match self {
+            SL_VIEW::SL_VIEW_SIDE_BY_SIDE => 2,
+            _ => 1,
+        }
+    }
+}
+
+impl SL_MEASURE {
+    /// The Mat type of the measure.
+    pub fn mat_type(&self) -> SL_MAT_TYPE {
+        match self {
+            SL_MEASURE::SL_MEASURE_DISPARITY
+            | SL_MEASURE::SL_MEASURE_DEPTH
+            | SL_MEASURE::SL_MEASURE_CONFIDENCE
+            | SL_MEASURE::SL_MEASURE_DISPARITY_RIGHT
+            | SL_MEASURE::SL_MEASURE_DEPTH_RIGHT => SL_MAT_TYPE_SL_MAT_TYPE_F32_C1,
+            SL_MEASURE::SL_MEASURE_XYZ
+            | SL_MEASURE::SL_MEASURE_XYZRGBA
+            | SL_MEASURE::SL_MEASURE_XYZBGRA
+            | SL_MEASURE::SL_MEASURE_XYZARGB
+            | SL_MEASURE::SL_MEASURE_XYZABGR
+            | SL_MEASURE::SL_MEASURE_NORMALS
+            | SL_MEASURE::SL_MEASURE_XYZ_RIGHT
+            | SL_MEASURE::SL_MEASURE_XYZRGBA_RIGHT
+            | SL_MEASURE::SL_MEASURE_XYZBGRA_RIGHT
+            | SL_MEASURE::SL_MEASURE_XYZARGB_RIGHT
+            | SL_MEASURE::SL_MEASURE_XYZABGR_RIGHT
+            | SL_MEASURE::SL_MEASURE_NORMALS_RIGHT => SL_MAT_TYPE_SL_MAT_TYPE_F32_C4,
+            SL_MEASURE::SL_MEASURE_DEPTH_U16_MM | SL_MEASURE::SL_MEASURE_DEPTH_U16_MM_RIGHT => {
+                SL_MAT_TYPE_SL_MAT_TYPE_U16_C1
+            }
+        }
+    }
+
+    /// Checks whether the measure is of the right sensor.
+    pub fn is_right(&self) -> bool {
+        matches!(
+            self,
+            SL_MEASURE::SL_MEASURE_DISPARITY_RIGHT
+                | SL_MEASURE::SL_MEASURE_DEPTH_RIGHT
+                | SL_MEASURE::SL_MEASURE_XYZ_RIGHT
+                | SL_MEASURE::SL_MEASURE_XYZRGBA_RIGHT
+                | SL_MEASURE::SL_MEASURE_XYZBGRA_RIGHT
+                | SL_MEASURE::SL_MEASURE_XYZARGB_RIGHT
+                | SL_MEASURE::SL_MEASURE_XYZABGR_RIGHT
+                | SL_MEASURE::SL_MEASURE_NORMALS_RIGHT
+                | SL_MEASURE::SL_MEASURE_DEPTH_U16_MM_RIGHT
+        )
+    }
+}
+
 impl Display for SL_ERROR_CODE {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let text = match self {
@@ -4,4 +4,6 @@
 
 mod bindings;
 mod ext;
+#[cfg(feature = "serde")]
+mod serde_impls;
 pub use bindings::*;
@@ -0,0 +1,210 @@
+use crate::{
+    SL_InitParameters, SL_CAMERA_STATE, SL_COORDINATE_SYSTEM, SL_DEPTH_MODE, SL_FLIP_MODE,
+    SL_INPUT_TYPE, SL_MEM, SL_MODEL, SL_RESOLUTION, SL_SENSING_MODE, SL_SIDE, SL_STREAMING_CODEC,
+    SL_SVO_COMPRESSION_MODE, SL_UNIT, SL_VIDEO_SETTINGS, SL_VIEW,
+};
+use serde::{de::Error as _, ser::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use std::{borrow::Cow, os::raw::c_int};
+
+/// Implements serde traits on a C enum using the names without the type
+/// prefix, e.g. `SL_RESOLUTION_HD720` becomes `"HD720"`.
+///
+/// Sentinel variants listed after `sentinel` have no name. They fail to
+/// serialize and no string deserializes into them.
+macro_rules! impl_serde_for_enum {
+    ($ty:ident { $($variant:ident => $name:literal),* $(,)? }) => {
+        impl_serde_for_enum!($ty { $($variant => $name),* } sentinel {});
+    };
+    ($ty:ident { $($variant:ident => $name:literal),* $(,)? } sentinel { $($sentinel:ident),* $(,)? }) => {
+        impl Serialize for $ty {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                let name = match self {
+                    $($ty::$variant => $name,)*
+                    $($ty::$sentinel => {
+                        return Err(S::Error::custom(concat!(
+                            stringify!($sentinel),
+                            " is not a valid value"
+                        )))
+                    })*
+                };
+                serializer.serialize_str(name)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                let name = Cow::<str>::deserialize(deserializer)?;
+                let value = match &*name {
+                    $($name => $ty::$variant,)*
+                    name => return Err(D::Error::unknown_variant(name, &[$($name),*])),
+                };
+                Ok(value)
+            }
+        }
+    };
+}
+
+impl_serde_for_enum!(SL_RESOLUTION {
+    SL_RESOLUTION_HD2K => "HD2K",
+    SL_RESOLUTION_HD1080 => "HD1080",
+    SL_RESOLUTION_HD720 => "HD720",
+    SL_RESOLUTION_VGA => "VGA",
+});
+
+impl_serde_for_enum!(SL_DEPTH_MODE {
+    SL_DEPTH_MODE_NONE => "NONE",
+    SL_DEPTH_MODE_PERFORMANCE => "PERFORMANCE",
+    SL_DEPTH_MODE_QUALITY => "QUALITY",
+    SL_DEPTH_MODE_ULTRA => "ULTRA",
+});
+
+impl_serde_for_enum!(SL_INPUT_TYPE {
+    SL_INPUT_TYPE_USB => "USB",
+    SL_INPUT_TYPE_SVO => "SVO",
+    SL_INPUT_TYPE_STREAM => "STREAM",
+});
+
+impl_serde_for_enum!(SL_MODEL {
+    SL_MODEL_ZED => "ZED",
+    SL_MODEL_ZED_M => "ZED_M",
+    SL_MODEL_ZED2 => "ZED2",
+    SL_MODEL_ZED2i => "ZED2i",
+});
+
+impl_serde_for_enum!(SL_CAMERA_STATE {
+    SL_CAMERA_STATE_AVAILABLE => "AVAILABLE",
+    SL_CAMERA_STATE_NOT_AVAILABLE => "NOT_AVAILABLE",
+});
+
+impl_serde_for_enum!(SL_VIDEO_SETTINGS {
+    SL_VIDEO_SETTINGS_BRIGHTNESS => "BRIGHTNESS",
+    SL_VIDEO_SETTINGS_CONTRAST => "CONTRAST",
+    SL_VIDEO_SETTINGS_HUE => "HUE",
+    SL_VIDEO_SETTINGS_SATURATION => "SATURATION",
+    SL_VIDEO_SETTINGS_SHARPNESS => "SHARPNESS",
+    SL_VIDEO_SETTINGS_GAMMA => "GAMMA",
+    SL_VIDEO_SETTINGS_GAIN => "GAIN",
+    SL_VIDEO_SETTINGS_EXPOSURE => "EXPOSURE",
+    SL_VIDEO_SETTINGS_AEC_AGC => "AEC_AGC",
+    SL_VIDEO_SETTINGS_AEC_AGC_ROI => "AEC_AGC_ROI",
+    SL_VIDEO_SETTINGS_WHITEBALANCE_TEMPERATURE => "WHITEBALANCE_TEMPERATURE",
+    SL_VIDEO_SETTINGS_WHITEBALANCE_AUTO => "WHITEBALANCE_AUTO",
+    SL_VIDEO_SETTINGS_LED_STATUS => "LED_STATUS",
+} sentinel {
+    SL_VIDEO_SETTINGS_LAST,
+});
+
+impl_serde_for_enum!(SL_SIDE {
+    SL_SIDE_LEFT => "LEFT",
+    SL_SIDE_RIGHT => "RIGHT",
+    SL_SIDE_BOTH => "BOTH",
+});
+
+impl_serde_for_enum!(SL_VIEW {
+    SL_VIEW_LEFT => "LEFT",
+    SL_VIEW_RIGHT => "RIGHT",
+    SL_VIEW_LEFT_GRAY => "LEFT_GRAY",
+    SL_VIEW_RIGHT_GRAY => "RIGHT_GRAY",
+    SL_VIEW_LEFT_UNRECTIFIED => "LEFT_UNRECTIFIED",
+    SL_VIEW_RIGHT_UNRECTIFIED => "RIGHT_UNRECTIFIED",
+    SL_VIEW_LEFT_UNRECTIFIED_GRAY => "LEFT_UNRECTIFIED_GRAY",
+    SL_VIEW_RIGHT_UNRECTIFIED_GRAY => "RIGHT_UNRECTIFIED_GRAY",
+    SL_VIEW_SIDE_BY_SIDE => "SIDE_BY_SIDE",
+    SL_VIEW_DEPTH => "DEPTH",
+    SL_VIEW_CONFIDENCE => "CONFIDENCE",
+    SL_VIEW_NORMALS => "NORMALS",
+    SL_VIEW_DEPTH_RIGHT => "DEPTH_RIGHT",
+    SL_VIEW_NORMALS_RIGHT => "NORMALS_RIGHT",
+});
+
+impl_serde_for_enum!(SL_MEM {
+    SL_MEM_CPU => "CPU",
+    SL_MEM_GPU => "GPU",
+});
+
+impl_serde_for_enum!(SL_FLIP_MODE {
+    SL_FLIP_MODE_OFF => "OFF",
+    SL_FLIP_MODE_ON => "ON",
+    SL_FLIP_MODE_AUTO => "AUTO",
+});
+
+impl_serde_for_enum!(SL_UNIT {
+    SL_UNIT_MILLIMETER => "MILLIMETER",
+    SL_UNIT_CENTIMETER => "CENTIMETER",
+    SL_UNIT_METER => "METER",
+    SL_UNIT_INCH => "INCH",
+    SL_UNIT_FOOT => "FOOT",
+});
+
+impl_serde_for_enum!(SL_COORDINATE_SYSTEM {
+    SL_COORDINATE_SYSTEM_IMAGE => "IMAGE",
+    SL_COORDINATE_SYSTEM_LEFT_HANDED_Y_UP => "LEFT_HANDED_Y_UP",
+    SL_COORDINATE_SYSTEM_RIGHT_HANDED_Y_UP => "RIGHT_HANDED_Y_UP",
+    SL_COORDINATE_SYSTEM_RIGHT_HANDED_Z_UP => "RIGHT_HANDED_Z_UP",
+    SL_COORDINATE_SYSTEM_LEFT_HANDED_Z_UP => "LEFT_HANDED_Z_UP",
+    SL_COORDINATE_SYSTEM_RIGHT_HANDED_Z_UP_X_FWD => "RIGHT_HANDED_Z_UP_X_FWD",
+});
+
+impl_serde_for_enum!(SL_STREAMING_CODEC {
+    SL_STREAMING_CODEC_H264 => "H264",
+    SL_STREAMING_CODEC_H265 => "H265",
+});
+
+impl_serde_for_enum!(SL_SVO_COMPRESSION_MODE {
+    SL_SVO_COMPRESSION_MODE_LOSSLESS => "LOSSLESS",
+    SL_SVO_COMPRESSION_MODE_H264 => "H264",
+    SL_SVO_COMPRESSION_MODE_H265 => "H265",
+});
+
+impl_serde_for_enum!(SL_SENSING_MODE {
+    SL_SENSING_MODE_STANDARD => "STANDARD",
+    SL_SENSING_MODE_FILL => "FILL",
+});
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "SL_InitParameters", default = "SL_InitParameters::default")]
+struct InitParametersDef {
+    input_type: SL_INPUT_TYPE,
+    resolution: SL_RESOLUTION,
+    camera_fps: c_int,
+    camera_device_id: c_int,
+    camera_image_flip: SL_FLIP_MODE,
+    camera_disable_self_calib: bool,
+    enable_right_side_measure: bool,
+    svo_real_time_mode: bool,
+    depth_mode: SL_DEPTH_MODE,
+    depth_stabilization: bool,
+    depth_minimum_distance: f32,
+    depth_maximum_distance: f32,
+    coordinate_unit: SL_UNIT,
+    coordinate_system: SL_COORDINATE_SYSTEM,
+    sdk_gpu_id: c_int,
+    sdk_verbose: c_int,
+    sensors_required: bool,
+    enable_image_enhancement: bool,
+    open_timeout_sec: f32,
+}
+
+impl Serialize for SL_InitParameters {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        InitParametersDef::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SL_InitParameters {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        InitParametersDef::deserialize(deserializer)
+    }
+}
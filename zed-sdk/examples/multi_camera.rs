@@ -26,9 +26,13 @@ fn main() -> Result<()> {
         let items: Vec<String> = devices
             .iter()
             .map(|dev| {
+                let model = match dev.model {
+                    Some(model) => model.to_string(),
+                    None => "unknown".to_string(),
+                };
                 format!(
                     "id={}\tsn={}\tmodel={}\tstate={}\t",
-                    dev.id, dev.sn, dev.camera_model, dev.camera_state
+                    dev.id, dev.serial_number, model, dev.state
                 )
             })
             .collect();
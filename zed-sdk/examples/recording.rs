@@ -1,7 +1,7 @@
 use anyhow::{bail, Result};
 use chrono::{Local, SecondsFormat};
 use dialoguer as dial;
-use sdk::{CameraBuilder, Mem, View};
+use sdk::{pixel_format::U8C1, CameraBuilder, Mat, View};
 use std::sync::{
     atomic::{AtomicBool, Ordering::*},
     Arc,
@@ -21,9 +21,13 @@ fn main() -> Result<()> {
         let items: Vec<String> = devices
             .iter()
             .map(|dev| {
+                let model = match dev.model {
+                    Some(model) => model.to_string(),
+                    None => "unknown".to_string(),
+                };
                 format!(
                     "id={}\tsn={}\tmodel={}\tstate={}\t",
-                    dev.id, dev.sn, dev.camera_model, dev.camera_state
+                    dev.id, dev.serial_number, model, dev.state
                 )
             })
             .collect();
@@ -59,8 +63,7 @@ fn main() -> Result<()> {
 
     while !terminate.load(SeqCst) {
         let mut grab = camera.grab(Default::default())?;
-        let values =
-            grab.retrieve_image_to_vec(View::SL_VIEW_LEFT_GRAY, Mem::SL_MEM_CPU, (100, 200))?;
+        let values: Mat<U8C1> = grab.retrieve_image_to_mat(View::SL_VIEW_LEFT_GRAY, (100, 200))?;
         // let output_file = format!("{}.jpg", make_timestamp());
         // grab.save_current_image(View::SL_VIEW_LEFT, output_file)?;
     }
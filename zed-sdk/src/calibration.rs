@@ -0,0 +1,536 @@
+use crate::{
+    common::*, ensure, CalibrationParameters, CoordinateSystem, PinholeModel, Quaternion, Result,
+    Side, Transform, Unit, Vector3,
+};
+use std::fmt::Write;
+
+/// The calibration of a stereo camera, exported to the formats of common
+/// vision tools.
+///
+/// The camera frames follow the image coordinate system, i.e. x right,
+/// y down and z forward, which OpenCV, ROS and Kalibr expect, whatever the
+/// coordinate system the camera is opened with. Translations are in meters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StereoCalibration {
+    pub left: PinholeModel,
+    pub right: PinholeModel,
+    /// Maps points in the left camera frame into the right camera frame,
+    /// like the `R` and `T` of OpenCV.
+    pub left_to_right: Transform,
+    /// Maps points in the IMU frame into the left camera frame, or `None`
+    /// if the camera has no IMU.
+    pub camera_imu: Option<Transform>,
+}
+
+impl StereoCalibration {
+    /// Creates the calibration from the SDK parameters, which are expressed
+    /// in the coordinate system and unit the camera is opened with.
+    ///
+    /// The SDK gives the pose of the right camera in the left camera frame,
+    /// which is converted to the image coordinate system and inverted into
+    /// [left_to_right](Self::left_to_right).
+    pub fn new(
+        params: &CalibrationParameters,
+        unit: Unit,
+        system: CoordinateSystem,
+    ) -> Result<Self> {
+        let sys::SL_Vector4 { x, y, z, w } = params.rotation;
+        let scale = unit.in_meters();
+        let translation = Vector3 {
+            x: params.translation.x * scale,
+            y: params.translation.y * scale,
+            z: params.translation.z * scale,
+        };
+        let right_pose = Transform::from_quaternion(Quaternion { x, y, z, w }, translation)
+            .to_image_frame(system)?;
+
+        Ok(Self {
+            left: PinholeModel::new(&params.left_cam),
+            right: PinholeModel::new(&params.right_cam),
+            left_to_right: right_pose.inverse(),
+            camera_imu: None,
+        })
+    }
+
+    /// Sets the transform from the IMU frame to the left camera frame, in
+    /// the image coordinate system with the translation in meters.
+    pub fn with_camera_imu(self, camera_imu: Transform) -> Self {
+        Self {
+            camera_imu: Some(camera_imu),
+            ..self
+        }
+    }
+
+    /// Gets the distance between the optical centers in meters.
+    pub fn baseline(&self) -> f32 {
+        let [x, y, z] = self.left_to_right.translation;
+        (x * x + y * y + z * z).sqrt()
+    }
+
+    /// Writes the calibration in the OpenCV FileStorage YAML format, with
+    /// the `M1`, `D1`, `M2`, `D2`, `R` and `T` matrices named as by the
+    /// OpenCV stereo calibration sample.
+    pub fn to_opencv_yaml(&self) -> String {
+        let (width, height) = self.left.resolution();
+        let mut out = String::new();
+
+        writeln!(out, "%YAML:1.0").unwrap();
+        writeln!(out, "---").unwrap();
+        writeln!(out, "image_width: {}", width).unwrap();
+        writeln!(out, "image_height: {}", height).unwrap();
+        write_opencv_matrix(&mut out, "M1", 3, 3, &camera_matrix(&self.left));
+        write_opencv_matrix(&mut out, "D1", 1, 5, &self.left.distortion);
+        write_opencv_matrix(&mut out, "M2", 3, 3, &camera_matrix(&self.right));
+        write_opencv_matrix(&mut out, "D2", 1, 5, &self.right.distortion);
+        write_opencv_matrix(&mut out, "R", 3, 3, &flatten(&self.left_to_right.rotation));
+        write_opencv_matrix(
+            &mut out,
+            "T",
+            3,
+            1,
+            &self.left_to_right.translation.map(|v| v as f64),
+        );
+        writeln!(out, "baseline: {}", self.baseline()).unwrap();
+        if let Some(camera_imu) = &self.camera_imu {
+            write_opencv_matrix(
+                &mut out,
+                "T_cam_imu",
+                4,
+                4,
+                &flatten(&camera_imu.to_matrix()),
+            );
+        }
+
+        out
+    }
+
+    /// Writes the calibration of one eye in the ROS `CameraInfo` YAML
+    /// format read by `camera_calibration_parsers`.
+    ///
+    /// As the ZED ROS wrapper does, `R` is the identity for the left eye
+    /// and the stereo rotation for the right eye, and `P` holds the
+    /// intrinsics of the eye, with `-fx * baseline` for the right eye. With
+    /// rectified parameters, both rotations are the identity. The camera
+    /// name is written as a quoted YAML string.
+    pub fn to_ros_camera_info(&self, side: Side, camera_name: &str) -> Result<String> {
+        ensure!(
+            side != Side::SL_SIDE_BOTH,
+            "expect the left or right side for the camera info"
+        );
+        let (model, rotation, tx) = match side {
+            Side::SL_SIDE_LEFT => (&self.left, Transform::IDENTITY.rotation, 0.0),
+            _ => (
+                &self.right,
+                self.left_to_right.rotation,
+                -self.right.fx * self.baseline(),
+            ),
+        };
+        let (width, height) = model.resolution();
+        let projection = [
+            [model.fx, 0.0, model.cx, tx],
+            [0.0, model.fy, model.cy, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+        ];
+
+        let mut out = String::new();
+        writeln!(out, "image_width: {}", width).unwrap();
+        writeln!(out, "image_height: {}", height).unwrap();
+        writeln!(out, "camera_name: {}", yaml_string(camera_name)).unwrap();
+        write_ros_matrix(&mut out, "camera_matrix", 3, 3, &camera_matrix(model));
+        writeln!(out, "distortion_model: plumb_bob").unwrap();
+        write_ros_matrix(&mut out, "distortion_coefficients", 1, 5, &model.distortion);
+        write_ros_matrix(&mut out, "rectification_matrix", 3, 3, &flatten(&rotation));
+        write_ros_matrix(&mut out, "projection_matrix", 3, 4, &flatten(&projection));
+        Ok(out)
+    }
+
+    /// Writes the calibration in the Kalibr camchain YAML format.
+    ///
+    /// Kalibr's `radtan` model has no `k3`, so it is dropped. `T_cam_imu`
+    /// is written only if the camera-IMU transform is set.
+    pub fn to_kalibr_yaml(&self) -> String {
+        let mut out = String::new();
+        let camera_imu = self.camera_imu.as_ref();
+
+        write_kalibr_camera(&mut out, "cam0", &self.left, camera_imu, None);
+        let right_imu = camera_imu.map(|t| self.left_to_right.compose(t));
+        write_kalibr_camera(
+            &mut out,
+            "cam1",
+            &self.right,
+            right_imu.as_ref(),
+            Some(&self.left_to_right),
+        );
+        out
+    }
+}
+
+fn camera_matrix(model: &PinholeModel) -> Vec<f64> {
+    flatten(&[
+        [model.fx, 0.0, model.cx],
+        [0.0, model.fy, model.cy],
+        [0.0, 0.0, 1.0],
+    ])
+}
+
+fn flatten<const R: usize, const C: usize>(matrix: &[[f32; C]; R]) -> Vec<f64> {
+    matrix.iter().flatten().map(|&v| v as f64).collect()
+}
+
+fn join(values: &[f64]) -> String {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Quotes the text as a double-quoted YAML scalar.
+fn yaml_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn write_opencv_matrix(out: &mut String, name: &str, rows: usize, cols: usize, data: &[f64]) {
+    writeln!(out, "{}: !!opencv-matrix", name).unwrap();
+    writeln!(out, "   rows: {}", rows).unwrap();
+    writeln!(out, "   cols: {}", cols).unwrap();
+    writeln!(out, "   dt: d").unwrap();
+    writeln!(out, "   data: [ {} ]", join(data)).unwrap();
+}
+
+fn write_ros_matrix(out: &mut String, name: &str, rows: usize, cols: usize, data: &[f64]) {
+    writeln!(out, "{}:", name).unwrap();
+    writeln!(out, "  rows: {}", rows).unwrap();
+    writeln!(out, "  cols: {}", cols).unwrap();
+    writeln!(out, "  data: [{}]", join(data)).unwrap();
+}
+
+fn write_kalibr_camera(
+    out: &mut String,
+    name: &str,
+    model: &PinholeModel,
+    camera_imu: Option<&Transform>,
+    from_previous: Option<&Transform>,
+) {
+    let [k1, k2, p1, p2, _] = model.distortion;
+    let (width, height) = model.resolution();
+    let intrinsics = [model.fx, model.fy, model.cx, model.cy].map(|v| v as f64);
+
+    writeln!(out, "{}:", name).unwrap();
+    writeln!(out, "  camera_model: pinhole").unwrap();
+    writeln!(out, "  intrinsics: [{}]", join(&intrinsics)).unwrap();
+    writeln!(out, "  distortion_model: radtan").unwrap();
+    writeln!(out, "  distortion_coeffs: [{}]", join(&[k1, k2, p1, p2])).unwrap();
+    writeln!(out, "  resolution: [{}, {}]", width, height).unwrap();
+    if let Some(transform) = camera_imu {
+        write_kalibr_transform(out, "T_cam_imu", transform);
+    }
+    if let Some(transform) = from_previous {
+        write_kalibr_transform(out, "T_cn_cnm1", transform);
+    }
+}
+
+fn write_kalibr_transform(out: &mut String, name: &str, transform: &Transform) {
+    writeln!(out, "  {}:", name).unwrap();
+    for row in transform.to_matrix() {
+        writeln!(out, "  - [{}]", join(&row.map(|v| v as f64))).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CameraParameters;
+
+    fn camera(fx: f32, cx: f32, disto: [f64; 5]) -> CameraParameters {
+        CameraParameters {
+            fx,
+            fy: fx + 0.5,
+            cx,
+            cy: 361.25,
+            disto,
+            v_fov: 0.0,
+            h_fov: 0.0,
+            d_fov: 0.0,
+            image_size: sys::SL_Resolution {
+                width: 1280,
+                height: 720,
+            },
+        }
+    }
+
+    const IMAGE: CoordinateSystem = CoordinateSystem::SL_COORDINATE_SYSTEM_IMAGE;
+    const ANGLE: f32 = 0.01;
+
+    /// A small rotation about y and a 120 mm baseline in the image
+    /// coordinate system.
+    fn params() -> CalibrationParameters {
+        let angle = ANGLE;
+        CalibrationParameters {
+            left_cam: camera(700.5, 640.25, [-0.17, 0.027, 0.0005, -0.0003, 0.001]),
+            right_cam: camera(699.75, 635.5, [-0.16, 0.025, 0.0004, -0.0002, 0.002]),
+            rotation: sys::SL_Vector4 {
+                x: 0.0,
+                y: (angle / 2.0).sin(),
+                z: 0.0,
+                w: (angle / 2.0).cos(),
+            },
+            translation: Vector3 {
+                x: 120.0,
+                y: 0.5,
+                z: -0.25,
+            },
+        }
+    }
+
+    fn calibration() -> StereoCalibration {
+        let camera_imu = Transform {
+            translation: [-0.002, -0.023, 0.0],
+            ..Transform::IDENTITY
+        };
+        StereoCalibration::new(&params(), Unit::SL_UNIT_MILLIMETER, IMAGE)
+            .unwrap()
+            .with_camera_imu(camera_imu)
+    }
+
+    /// Reads the `count` bracketed lists of numbers following `key`.
+    fn lists(text: &str, key: &str, count: usize) -> Vec<Vec<f64>> {
+        let mut rest = &text[text.find(key).unwrap_or_else(|| panic!("no {}", key))..];
+        (0..count)
+            .map(|_| {
+                let start = rest.find('[').unwrap() + 1;
+                let end = start + rest[start..].find(']').unwrap();
+                let list = rest[start..end]
+                    .split(',')
+                    .map(|v| v.trim().parse().unwrap())
+                    .collect();
+                rest = &rest[end..];
+                list
+            })
+            .collect()
+    }
+
+    fn list(text: &str, key: &str) -> Vec<f64> {
+        lists(text, key, 1).remove(0)
+    }
+
+    fn scalar(text: &str, key: &str) -> f64 {
+        let line = text.lines().find(|l| l.starts_with(key)).unwrap();
+        line[key.len()..].trim().parse().unwrap()
+    }
+
+    fn assert_close(a: &[f64], b: &[f64]) {
+        assert_eq!(a.len(), b.len());
+        for (a, b) in a.iter().zip(b) {
+            assert!((a - b).abs() < 1e-6, "{:?} != {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn convert_sdk_parameters() {
+        let calib = calibration();
+        let [tx, ty, tz] = calib.left_to_right.translation;
+        assert!((tx + 0.12).abs() < 1e-3, "{}", tx);
+        assert!(ty.abs() < 1e-3 && tz.abs() < 2e-3);
+        assert!((calib.baseline() - 0.120001).abs() < 1e-5);
+
+        let round_trip = calib.left_to_right.inverse().inverse();
+        for (a, b) in round_trip
+            .rotation
+            .iter()
+            .zip(&calib.left_to_right.rotation)
+        {
+            assert_close(&a.map(|v| v as f64), &b.map(|v| v as f64));
+        }
+    }
+
+    #[test]
+    fn convert_coordinate_systems() {
+        let expected = calibration().left_to_right;
+        let (sin, cos) = ((ANGLE / 2.0).sin(), (ANGLE / 2.0).cos());
+
+        // The image y axis is the ROS -z axis and the OpenGL -y axis.
+        let ros = CalibrationParameters {
+            rotation: sys::SL_Vector4 {
+                x: 0.0,
+                y: 0.0,
+                z: -sin,
+                w: cos,
+            },
+            translation: Vector3 {
+                x: -0.25,
+                y: -120.0,
+                z: -0.5,
+            },
+            ..params()
+        };
+        let opengl = CalibrationParameters {
+            rotation: sys::SL_Vector4 {
+                x: 0.0,
+                y: -sin,
+                z: 0.0,
+                w: cos,
+            },
+            translation: Vector3 {
+                x: 120.0,
+                y: -0.5,
+                z: 0.25,
+            },
+            ..params()
+        };
+
+        for (params, system) in [
+            (
+                ros,
+                CoordinateSystem::SL_COORDINATE_SYSTEM_RIGHT_HANDED_Z_UP_X_FWD,
+            ),
+            (
+                opengl,
+                CoordinateSystem::SL_COORDINATE_SYSTEM_RIGHT_HANDED_Y_UP,
+            ),
+        ] {
+            let calib = StereoCalibration::new(&params, Unit::SL_UNIT_MILLIMETER, system).unwrap();
+            let to_f64 = |t: &Transform| flatten(&t.to_matrix());
+            assert_close(&to_f64(&calib.left_to_right), &to_f64(&expected));
+        }
+    }
+
+    #[test]
+    fn opencv_round_trip() {
+        let calib = calibration();
+        let text = calib.to_opencv_yaml();
+
+        assert!(text.starts_with("%YAML:1.0\n---\n"));
+        assert_eq!(scalar(&text, "image_width:"), 1280.0);
+        assert_eq!(list(&text, "M1:"), camera_matrix(&calib.left).to_vec());
+        assert_eq!(list(&text, "D1:"), calib.left.distortion.to_vec());
+        assert_eq!(list(&text, "M2:"), camera_matrix(&calib.right).to_vec());
+        assert_eq!(list(&text, "D2:"), calib.right.distortion.to_vec());
+        assert_eq!(list(&text, "\nR:"), flatten(&calib.left_to_right.rotation));
+        assert_eq!(
+            list(&text, "\nT:"),
+            calib.left_to_right.translation.map(|v| v as f64).to_vec()
+        );
+        assert_eq!(scalar(&text, "baseline:") as f32, calib.baseline());
+        assert_eq!(
+            list(&text, "T_cam_imu:"),
+            flatten(&calib.camera_imu.unwrap().to_matrix())
+        );
+    }
+
+    #[test]
+    fn ros_camera_info_round_trip() {
+        let calib = calibration();
+        let left = calib
+            .to_ros_camera_info(Side::SL_SIDE_LEFT, "zed_left")
+            .unwrap();
+        let right = calib
+            .to_ros_camera_info(Side::SL_SIDE_RIGHT, "zed_right")
+            .unwrap();
+        assert!(calib.to_ros_camera_info(Side::SL_SIDE_BOTH, "zed").is_err());
+
+        assert!(left.contains("camera_name: \"zed_left\"\n"));
+        assert!(left.contains("distortion_model: plumb_bob\n"));
+        assert_eq!(scalar(&left, "image_height:"), 720.0);
+        assert_eq!(
+            list(&left, "camera_matrix:"),
+            camera_matrix(&calib.left).to_vec()
+        );
+        assert_eq!(
+            list(&right, "distortion_coefficients:"),
+            calib.right.distortion.to_vec()
+        );
+        assert_eq!(
+            list(&left, "rectification_matrix:"),
+            flatten(&Transform::IDENTITY.rotation)
+        );
+        assert_eq!(
+            list(&right, "rectification_matrix:"),
+            flatten(&calib.left_to_right.rotation)
+        );
+
+        let p = list(&right, "projection_matrix:");
+        let r = &calib.right;
+        let tx = (-r.fx * calib.baseline()) as f64;
+        let expected = [r.fx, 0.0, r.cx].map(|v| v as f64);
+        assert_eq!(&p[..3], &expected);
+        assert_eq!(p[3], tx);
+        assert_eq!(list(&left, "projection_matrix:")[3], 0.0);
+    }
+
+    #[test]
+    fn kalibr_round_trip() {
+        let calib = calibration();
+        let text = calib.to_kalibr_yaml();
+        let cam1 = &text[text.find("cam1:").unwrap()..];
+
+        let intrinsics = |m: &PinholeModel| [m.fx, m.fy, m.cx, m.cy].map(|v| v as f64).to_vec();
+        assert_eq!(list(&text, "intrinsics:"), intrinsics(&calib.left));
+        assert_eq!(list(cam1, "intrinsics:"), intrinsics(&calib.right));
+        assert_eq!(
+            list(cam1, "distortion_coeffs:"),
+            calib.right.distortion[..4].to_vec()
+        );
+        assert_eq!(list(&text, "resolution:"), vec![1280.0, 720.0]);
+
+        let matrix = |t: &Transform| {
+            t.to_matrix()
+                .map(|row| row.map(|v| v as f64).to_vec())
+                .to_vec()
+        };
+        let camera_imu = calib.camera_imu.unwrap();
+        assert_eq!(lists(&text, "T_cam_imu:", 4), matrix(&camera_imu));
+        assert_eq!(lists(cam1, "T_cn_cnm1:", 4), matrix(&calib.left_to_right));
+        assert!(!text[..text.find("cam1:").unwrap()].contains("T_cn_cnm1"));
+    }
+
+    #[test]
+    fn kalibr_right_camera_imu() {
+        // A quarter turn about y between the cameras and a quarter turn
+        // about z between the IMU and the left camera, which do not commute.
+        let calib = StereoCalibration {
+            left_to_right: Transform {
+                rotation: [[0.0, 0.0, 1.0], [0.0, 1.0, 0.0], [-1.0, 0.0, 0.0]],
+                translation: [-0.12, 0.0, 0.0],
+            },
+            camera_imu: Some(Transform {
+                rotation: [[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]],
+                translation: [0.01, 0.02, 0.03],
+            }),
+            ..calibration()
+        };
+        let text = calib.to_kalibr_yaml();
+        let cam1 = &text[text.find("cam1:").unwrap()..];
+
+        let expected = [
+            [0.0, 0.0, 1.0, -0.09],
+            [1.0, 0.0, 0.0, 0.02],
+            [0.0, 1.0, 0.0, -0.01],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+        for (row, expected) in lists(cam1, "T_cam_imu:", 4).iter().zip(&expected) {
+            assert_close(row, expected);
+        }
+    }
+
+    #[test]
+    fn quote_yaml_strings() {
+        assert_eq!(yaml_string("zed_left"), r#""zed_left""#);
+        assert_eq!(yaml_string("a: b # c"), r#""a: b # c""#);
+        assert_eq!(yaml_string("say \"hi\"\\"), r#""say \"hi\"\\""#);
+        assert_eq!(yaml_string("a\nb\u{7}"), r#""a\nb\u0007""#);
+    }
+}
@@ -1,7 +1,12 @@
-use super::{input_source as is, recording_state as rs, streaming_state as ss};
+use super::{input_source as is, recording_state as rs, slot::CameraSlot, streaming_state as ss};
 use crate::{
-    common::*, error::code_to_result, utils::osstr_to_cstr, Camera, CoordinateSystem, DepthMode,
-    FlipMode, InitParameters, InputType, Resolution, Result, Unit,
+    common::*,
+    ensure,
+    error::{code_to_result, Error, ErrorContext, Operation},
+    get_device_list,
+    utils::osstr_to_cstr,
+    Camera, CoordinateSystem, DepthMode, ErrorCode, FlipMode, InitParameters, InputType,
+    Resolution, Result, Unit,
 };
 
 #[derive(Debug, Clone)]
@@ -10,6 +15,16 @@ pub struct CameraBuilder<'a> {
     pub opt_settings_path: Option<Cow<'a, Path>>,
     pub opencv_calib_path: Option<Cow<'a, Path>>,
     pub params: InitParameters,
+    rejected: Rejected,
+}
+
+/// The values passed to setters that do not fit in [InitParameters], kept
+/// to be reported when the camera is opened.
+#[derive(Debug, Clone, Copy, Default)]
+struct Rejected {
+    depth_minimum_distance: Option<f32>,
+    depth_maximum_distance: Option<f32>,
+    sdk_gpu_id: Option<u32>,
 }
 
 impl<'a> CameraBuilder<'a> {
@@ -19,6 +34,7 @@ impl<'a> CameraBuilder<'a> {
             opt_settings_path: None,
             opencv_calib_path: None,
             params: Default::default(),
+            rejected: Default::default(),
         }
     }
 
@@ -96,6 +112,157 @@ impl<'a> CameraBuilder<'a> {
         }
     }
 
+    pub fn enable_right_side_measure(self, yes: bool) -> Self {
+        Self {
+            params: InitParameters {
+                enable_right_side_measure: yes,
+                ..self.params
+            },
+            ..self
+        }
+    }
+
+    pub fn svo_real_time_mode(self, yes: bool) -> Self {
+        Self {
+            params: InitParameters {
+                svo_real_time_mode: yes,
+                ..self.params
+            },
+            ..self
+        }
+    }
+
+    pub fn depth_stabilization(self, yes: bool) -> Self {
+        Self {
+            params: InitParameters {
+                depth_stabilization: yes,
+                ..self.params
+            },
+            ..self
+        }
+    }
+
+    /// Sets the minimum depth distance in the coordinate unit.
+    ///
+    /// `None` picks the default of the camera model, and `Some(0.0)` picks
+    /// the smallest distance supported by the camera model. It cannot be
+    /// negative or greater than 3 meters.
+    pub fn depth_minimum_distance<D>(self, dist: D) -> Self
+    where
+        D: Into<Option<f32>>,
+    {
+        let dist = dist.into();
+        Self {
+            params: InitParameters {
+                depth_minimum_distance: dist.unwrap_or(-1.0),
+                ..self.params
+            },
+            rejected: Rejected {
+                depth_minimum_distance: dist.filter(|&dist| dist < 0.0),
+                ..self.rejected
+            },
+            ..self
+        }
+    }
+
+    /// Sets the maximum depth distance in the coordinate unit.
+    ///
+    /// `None` picks the default of the camera model. It must be positive.
+    pub fn depth_maximum_distance<D>(self, dist: D) -> Self
+    where
+        D: Into<Option<f32>>,
+    {
+        let dist = dist.into();
+        Self {
+            params: InitParameters {
+                depth_maximum_distance: dist.unwrap_or(-1.0),
+                ..self.params
+            },
+            rejected: Rejected {
+                depth_maximum_distance: dist.filter(|&dist| dist < 0.0),
+                ..self.rejected
+            },
+            ..self
+        }
+    }
+
+    /// Selects the GPU by its CUDA device index.
+    ///
+    /// `None` picks the most powerful GPU.
+    pub fn sdk_gpu_id<N>(self, id: N) -> Self
+    where
+        N: Into<Option<u32>>,
+    {
+        let id = id.into();
+        let raw_id = match id {
+            Some(id) => c_int::try_from(id).unwrap_or(c_int::MIN),
+            None => -1,
+        };
+
+        Self {
+            params: InitParameters {
+                sdk_gpu_id: raw_id,
+                ..self.params
+            },
+            rejected: Rejected {
+                sdk_gpu_id: id.filter(|_| raw_id < 0),
+                ..self.rejected
+            },
+            ..self
+        }
+    }
+
+    pub fn sdk_verbose(self, yes: bool) -> Self {
+        Self {
+            params: InitParameters {
+                sdk_verbose: yes as c_int,
+                ..self.params
+            },
+            ..self
+        }
+    }
+
+    pub fn sensors_required(self, yes: bool) -> Self {
+        Self {
+            params: InitParameters {
+                sensors_required: yes,
+                ..self.params
+            },
+            ..self
+        }
+    }
+
+    pub fn enable_image_enhancement(self, yes: bool) -> Self {
+        Self {
+            params: InitParameters {
+                enable_image_enhancement: yes,
+                ..self.params
+            },
+            ..self
+        }
+    }
+
+    /// Sets the time limit to open the camera.
+    ///
+    /// `None` retries endlessly until the camera is opened.
+    pub fn open_timeout<T>(self, timeout: T) -> Self
+    where
+        T: Into<Option<Duration>>,
+    {
+        let timeout = timeout
+            .into()
+            .map(|timeout| timeout.as_secs_f32())
+            .unwrap_or(-1.0);
+
+        Self {
+            params: InitParameters {
+                open_timeout_sec: timeout,
+                ..self.params
+            },
+            ..self
+        }
+    }
+
     pub fn output_file<P>(self, path: P) -> Self
     where
         P: Into<Cow<'a, Path>>,
@@ -130,131 +297,207 @@ impl<'a> CameraBuilder<'a> {
     where
         P: AsRef<Path>,
     {
-        let Self {
-            output_file,
-            opt_settings_path,
-            opencv_calib_path,
-            params,
-        } = self;
-
-        let mut params = InitParameters {
-            input_type: InputType::SL_INPUT_TYPE_SVO,
-            ..params
-        };
-        let output_file: Option<_> = output_file.map(|path| osstr_to_cstr(path.as_ref()));
-        let opt_settings_path: Option<_> =
-            opt_settings_path.map(|path| osstr_to_cstr(path.as_ref()));
-        let opencv_calib_path: Option<_> =
-            opencv_calib_path.map(|path| osstr_to_cstr(path.as_ref()));
-
-        let svo_path = osstr_to_cstr(svo_path.as_ref());
-
-        let code = unsafe {
-            sys::sl_open_camera(
-                0,
-                &mut params as *mut _,
-                svo_path.as_ptr(),
-                ptr::null(),
-                0,
-                output_file.map(|path| path.as_ptr()).unwrap_or(ptr::null()),
-                opt_settings_path
-                    .map(|path| path.as_ptr())
-                    .unwrap_or(ptr::null()),
-                opencv_calib_path
-                    .map(|path| path.as_ptr())
-                    .unwrap_or(ptr::null()),
-            )
+        let context = ErrorContext {
+            path: Some(svo_path.as_ref().to_owned()),
+            ..ErrorContext::new(Operation::Open)
         };
-        code_to_result(code as u32)?;
-
-        todo!("allocate new camera id");
+        self.open(InputType::SL_INPUT_TYPE_SVO, 0, context)
     }
 
     pub fn open_stream<A>(self, addr: A) -> Result<Camera<is::Stream, rs::Inactive, ss::Inactive>>
     where
         A: Into<SocketAddr>,
     {
-        let Self {
-            output_file,
-            opt_settings_path,
-            opencv_calib_path,
-            params,
-        } = self;
-
-        let mut params = InitParameters {
-            input_type: InputType::SL_INPUT_TYPE_STREAM,
-            ..params
+        let context = ErrorContext {
+            addr: Some(addr.into()),
+            ..ErrorContext::new(Operation::Open)
         };
-        let output_file: Option<_> = output_file.map(|path| osstr_to_cstr(path.as_ref()));
-        let opt_settings_path: Option<_> =
-            opt_settings_path.map(|path| osstr_to_cstr(path.as_ref()));
-        let opencv_calib_path: Option<_> =
-            opencv_calib_path.map(|path| osstr_to_cstr(path.as_ref()));
+        self.open(InputType::SL_INPUT_TYPE_STREAM, 0, context)
+    }
 
-        let addr = addr.into();
-        let ip = CString::new(addr.ip().to_string()).unwrap();
-        let port = addr.port();
+    pub fn open_usb(self, id: c_int) -> Result<Camera<is::USB, rs::Inactive, ss::Inactive>> {
+        let context = ErrorContext::new(Operation::Open);
+        self.open(InputType::SL_INPUT_TYPE_USB, id, context)
+    }
 
-        let code = unsafe {
-            sys::sl_open_camera(
-                0,
-                &mut params as *mut _,
-                ptr::null(),
-                ip.as_ptr(),
-                port as c_int,
-                output_file.map(|path| path.as_ptr()).unwrap_or(ptr::null()),
-                opt_settings_path
-                    .map(|path| path.as_ptr())
-                    .unwrap_or(ptr::null()),
-                opencv_calib_path
-                    .map(|path| path.as_ptr())
-                    .unwrap_or(ptr::null()),
-            )
+    /// Opens the USB camera with the serial number.
+    ///
+    /// Unlike the device index used by [open_usb](Self::open_usb), the
+    /// serial number does not change when cameras are replugged.
+    pub fn open_serial(self, sn: c_int) -> Result<Camera<is::USB, rs::Inactive, ss::Inactive>> {
+        let context = ErrorContext {
+            serial_number: Some(sn),
+            ..ErrorContext::new(Operation::Open)
         };
-        code_to_result(code as u32)?;
 
-        todo!("allocate new camera id");
+        let device = get_device_list()
+            .into_iter()
+            .find(|dev| dev.serial_number == sn)
+            // Reported as the SDK does for a missing camera, so that it is
+            // retried like an unplugged camera.
+            .ok_or_else(|| {
+                Error::Code(ErrorCode::SL_ERROR_CODE_CAMERA_NOT_DETECTED)
+                    .with_context(context.clone())
+            })?;
+        if !device.is_available() {
+            let err = Error::Desc(
+                format!("the camera with serial number {} is not available", sn).into(),
+            );
+            return Err(err.with_context(context));
+        }
+
+        self.open(InputType::SL_INPUT_TYPE_USB, device.id, context)
     }
 
-    pub fn open_usb(self, id: c_int) -> Result<Camera<is::USB, rs::Inactive, ss::Inactive>> {
+    fn open<I>(
+        self,
+        input_type: InputType,
+        device_id: c_int,
+        mut context: ErrorContext,
+    ) -> Result<Camera<I, rs::Inactive, ss::Inactive>> {
         let Self {
             output_file,
             opt_settings_path,
             opencv_calib_path,
             params,
+            rejected,
         } = self;
 
+        validate(&params, &rejected).map_err(|err| err.with_context(context.clone()))?;
+
         let mut params = InitParameters {
-            camera_device_id: id as i32,
-            input_type: InputType::SL_INPUT_TYPE_USB,
+            camera_device_id: device_id,
+            input_type,
             ..params
         };
+        let svo_path: Option<_> = context.path.as_deref().map(osstr_to_cstr);
+        let ip: Option<_> = context
+            .addr
+            .map(|addr| CString::new(addr.ip().to_string()).unwrap());
+        let port = context.addr.map(|addr| addr.port() as c_int).unwrap_or(0);
         let output_file: Option<_> = output_file.map(|path| osstr_to_cstr(path.as_ref()));
         let opt_settings_path: Option<_> =
             opt_settings_path.map(|path| osstr_to_cstr(path.as_ref()));
         let opencv_calib_path: Option<_> =
             opencv_calib_path.map(|path| osstr_to_cstr(path.as_ref()));
 
+        let slot = CameraSlot::acquire().map_err(|err| err.with_context(context.clone()))?;
+        context.camera_id = Some(slot.id());
+
         let code = unsafe {
             sys::sl_open_camera(
-                id as i32,
+                slot.id(),
                 &mut params as *mut _,
-                ptr::null(),
-                ptr::null(),
-                0,
-                output_file.map(|path| path.as_ptr()).unwrap_or(ptr::null()),
+                svo_path
+                    .as_ref()
+                    .map(|path| path.as_ptr())
+                    .unwrap_or(ptr::null()),
+                ip.as_ref().map(|ip| ip.as_ptr()).unwrap_or(ptr::null()),
+                port,
+                output_file
+                    .as_ref()
+                    .map(|path| path.as_ptr())
+                    .unwrap_or(ptr::null()),
                 opt_settings_path
+                    .as_ref()
                     .map(|path| path.as_ptr())
                     .unwrap_or(ptr::null()),
                 opencv_calib_path
+                    .as_ref()
                     .map(|path| path.as_ptr())
                     .unwrap_or(ptr::null()),
             )
         };
-        code_to_result(code as u32)?;
+        code_to_result(code).map_err(|err| err.with_context(context.clone()))?;
+
+        let mut camera = Camera::new(slot, input_type);
+        camera.inner.serial_number = context.serial_number;
+        camera.inner.input_path = context.path;
+        camera.inner.input_addr = context.addr;
+        camera.inner.coordinate_unit = params.coordinate_unit;
+        camera.inner.coordinate_system = params.coordinate_system;
+        Ok(camera)
+    }
+}
 
-        Ok(Camera::new(id))
+fn validate(params: &InitParameters, rejected: &Rejected) -> Result<()> {
+    if let Some(dist) = rejected.depth_minimum_distance {
+        return Err(Error::Desc(
+            format!(
+                "depth minimum distance must not be negative, but get {}",
+                dist
+            )
+            .into(),
+        ));
+    }
+    if let Some(dist) = rejected.depth_maximum_distance {
+        return Err(Error::Desc(
+            format!("depth maximum distance must be positive, but get {}", dist).into(),
+        ));
     }
+    if let Some(id) = rejected.sdk_gpu_id {
+        return Err(Error::Desc(format!("invalid GPU id {}", id).into()));
+    }
+
+    validate_params(params)
+}
+
+fn validate_params(params: &InitParameters) -> Result<()> {
+    let InitParameters {
+        camera_fps,
+        depth_minimum_distance: min_dist,
+        depth_maximum_distance: max_dist,
+        coordinate_unit,
+        sdk_gpu_id,
+        sdk_verbose,
+        open_timeout_sec,
+        ..
+    } = *params;
+
+    ensure!(camera_fps >= 0, "invalid camera fps {}", camera_fps);
+
+    if min_dist != -1.0 {
+        // Rounded so that 3 meters in millimeters is 3000 despite the f32
+        // precision of the unit.
+        let max_min_dist = ((3.0 / coordinate_unit.in_meters() as f64 * 1e3).round() / 1e3) as f32;
+        ensure!(
+            min_dist.is_finite() && (0.0..=max_min_dist).contains(&min_dist),
+            "depth minimum distance must be within 0 and {} in {:?}, but get {}",
+            max_min_dist,
+            coordinate_unit,
+            min_dist
+        );
+    }
+
+    if max_dist != -1.0 {
+        ensure!(
+            max_dist.is_finite() && max_dist > 0.0,
+            "depth maximum distance must be positive, but get {}",
+            max_dist
+        );
+    }
+
+    if min_dist != -1.0 && max_dist != -1.0 {
+        ensure!(
+            min_dist.total_cmp(&max_dist).is_lt(),
+            "depth minimum distance {} must be less than the maximum distance {}",
+            min_dist,
+            max_dist
+        );
+    }
+
+    ensure!(sdk_gpu_id >= -1, "invalid GPU id {}", sdk_gpu_id);
+    ensure!(sdk_verbose >= 0, "invalid SDK verbosity {}", sdk_verbose);
+
+    if open_timeout_sec != -1.0 {
+        ensure!(
+            open_timeout_sec.is_finite() && open_timeout_sec > 0.0,
+            "open timeout must be positive, but get {} seconds",
+            open_timeout_sec
+        );
+    }
+
+    Ok(())
 }
 
 impl<'a> Default for CameraBuilder<'a> {
@@ -262,3 +505,136 @@ impl<'a> Default for CameraBuilder<'a> {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(builder: CameraBuilder<'_>) -> Result<()> {
+        validate(&builder.params, &builder.rejected)
+    }
+
+    fn assert_rejected(builder: CameraBuilder<'_>, message: &str) {
+        match check(builder) {
+            Err(Error::Desc(desc)) => assert!(desc.contains(message), "{}", desc),
+            other => panic!("expect rejection with {:?}, but get {:?}", message, other),
+        }
+    }
+
+    #[test]
+    fn accept_defaults() {
+        check(CameraBuilder::new()).unwrap();
+        check(
+            CameraBuilder::new()
+                .depth_minimum_distance(0.0)
+                .depth_maximum_distance(None)
+                .sdk_gpu_id(0),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn reject_negative_distances() {
+        assert_rejected(
+            CameraBuilder::new().depth_minimum_distance(-1.0),
+            "must not be negative, but get -1",
+        );
+        assert_rejected(
+            CameraBuilder::new().depth_maximum_distance(-1.0),
+            "must be positive, but get -1",
+        );
+        assert_rejected(
+            CameraBuilder::new().depth_maximum_distance(0.0),
+            "must be positive",
+        );
+        check(
+            CameraBuilder::new()
+                .depth_minimum_distance(-1.0)
+                .depth_minimum_distance(None),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn limit_minimum_distance_in_unit() {
+        let meters = CameraBuilder::new().unit(Unit::SL_UNIT_METER);
+        check(meters.clone().depth_minimum_distance(3.0)).unwrap();
+        assert_rejected(
+            meters.depth_minimum_distance(3.5),
+            "within 0 and 3 in SL_UNIT_METER",
+        );
+
+        let millimeters = CameraBuilder::new().unit(Unit::SL_UNIT_MILLIMETER);
+        check(millimeters.clone().depth_minimum_distance(2999.0)).unwrap();
+        assert_rejected(
+            millimeters.depth_minimum_distance(3001.0),
+            "within 0 and 3000 in SL_UNIT_MILLIMETER",
+        );
+    }
+
+    #[test]
+    fn reject_inverted_distances() {
+        assert_rejected(
+            CameraBuilder::new()
+                .unit(Unit::SL_UNIT_METER)
+                .depth_minimum_distance(2.0)
+                .depth_maximum_distance(1.0),
+            "must be less than the maximum distance",
+        );
+        assert_rejected(
+            CameraBuilder::new().depth_minimum_distance(f32::NAN),
+            "depth minimum distance",
+        );
+    }
+
+    #[test]
+    fn report_original_gpu_id() {
+        assert_rejected(
+            CameraBuilder::new().sdk_gpu_id(u32::MAX),
+            &format!("invalid GPU id {}", u32::MAX),
+        );
+    }
+
+    #[test]
+    fn reject_invalid_raw_params() {
+        let builder = |params| CameraBuilder {
+            params,
+            ..CameraBuilder::new()
+        };
+        let defaults = InitParameters::default();
+
+        assert_rejected(
+            builder(InitParameters {
+                camera_fps: -1,
+                ..defaults
+            }),
+            "invalid camera fps",
+        );
+        assert_rejected(
+            builder(InitParameters {
+                sdk_gpu_id: -2,
+                ..defaults
+            }),
+            "invalid GPU id -2",
+        );
+        assert_rejected(
+            builder(InitParameters {
+                sdk_verbose: -1,
+                ..defaults
+            }),
+            "invalid SDK verbosity",
+        );
+        assert_rejected(
+            builder(InitParameters {
+                open_timeout_sec: 0.0,
+                ..defaults
+            }),
+            "open timeout must be positive",
+        );
+        check(builder(InitParameters {
+            open_timeout_sec: -1.0,
+            ..defaults
+        }))
+        .unwrap();
+    }
+}
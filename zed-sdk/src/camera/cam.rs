@@ -1,19 +1,26 @@
-use super::{input_source as is, recording_state as rs, streaming_state as ss};
+use super::{input_source as is, recording_state as rs, slot::CameraSlot, streaming_state as ss};
 use crate::{
-    common::*, ensure, error::code_to_result, utils::osstr_to_cstr, CalibrationParameters, Mem,
-    Model, RecordingParams, Result, RuntimeParameters, StreamingParameters, VideoSettings, View,
+    common::*,
+    ensure,
+    error::{code_to_result, CloseError, CloseStep, Error, ErrorContext, Operation, StateError},
+    pixel_format::PixelFormat,
+    utils::osstr_to_cstr,
+    CalibrationParameters, CoordinateSystem, InputType, Mat, Measure, MeasureBuffer, Mem, Model,
+    Quaternion, RecordingParams, RecordingState, Result, RuntimeParameters, RuntimeParams,
+    StereoCalibration, StreamingParameters, StreamingState, Transform, Unit, Vector3,
+    VideoSettings, View,
 };
 use std::os::raw::{c_uint, c_ulonglong, c_ushort};
 
 #[derive(Debug)]
 pub struct Camera<I, R, S> {
-    inner: Inner,
+    pub(super) inner: Inner,
     _phantom: PhantomData<(I, R, S)>,
 }
 
 impl<I, R, S> Camera<I, R, S> {
     pub fn id(&mut self) -> c_int {
-        self.inner.id
+        self.inner.slot.id()
     }
 
     pub fn resolution(&mut self) -> (usize, usize) {
@@ -32,13 +39,36 @@ impl<I, R, S> Camera<I, R, S> {
         unsafe { sys::sl_get_camera_fps(self.id()) }
     }
 
-    pub fn model(&mut self) -> Model {
-        unsafe {
-            let model = sys::sl_get_camera_model(self.id());
-            mem::transmute(model)
+    pub fn model(&mut self) -> Result<Model> {
+        let model = unsafe { sys::sl_get_camera_model(self.id()) };
+        Model::try_from(model)
+            .map_err(|model| Error::Desc(format!("unknown camera model {}", model).into()))
+    }
+
+    /// Gets the serial number of the camera.
+    ///
+    /// It returns the serial number given to
+    /// [CameraBuilder::open_serial](crate::CameraBuilder::open_serial) if
+    /// the camera is opened that way.
+    pub fn serial_number(&mut self) -> c_int {
+        match self.inner.serial_number {
+            Some(sn) => sn,
+            None => unsafe { sys::sl_get_zed_serial(self.id()) },
         }
     }
 
+    /// Gets the unit of the depth and point cloud measures given by
+    /// [CameraBuilder::unit](crate::CameraBuilder::unit).
+    pub fn coordinate_unit(&self) -> Unit {
+        self.inner.coordinate_unit
+    }
+
+    /// Gets the coordinate system of the measures and poses given by
+    /// [CameraBuilder::coordinate_system](crate::CameraBuilder::coordinate_system).
+    pub fn coordinate_system(&self) -> CoordinateSystem {
+        self.inner.coordinate_system
+    }
+
     pub fn firmware(&mut self) -> c_int {
         unsafe { sys::sl_get_camera_firmware(self.id()) }
     }
@@ -53,6 +83,14 @@ impl<I, R, S> Camera<I, R, S> {
         }
     }
 
+    pub fn set_settings(&mut self, option: VideoSettings, value: c_int) {
+        let id = self.id();
+
+        unsafe {
+            sys::sl_set_camera_settings(id, option, value);
+        }
+    }
+
     pub fn calibration_parameters(&mut self, raw: bool) -> &CalibrationParameters {
         let id = self.id();
 
@@ -62,27 +100,97 @@ impl<I, R, S> Camera<I, R, S> {
                 .unwrap()
         }
     }
+
+    /// Gets the calibration of both eyes and the IMU, converted to the image
+    /// coordinate system with the translations in meters.
+    ///
+    /// The camera-IMU transform is `None` for the ZED, which has no IMU.
+    pub fn stereo_calibration(&mut self, raw: bool) -> Result<StereoCalibration> {
+        let unit = self.coordinate_unit();
+        let system = self.coordinate_system();
+        let params = *self.calibration_parameters(raw);
+        let calibration = StereoCalibration::new(&params, unit, system)?;
+
+        if self.model()? == Model::SL_MODEL_ZED {
+            return Ok(calibration);
+        }
+
+        let mut camera_imu = self.camera_imu_transform().to_image_frame(system)?;
+        camera_imu.translation = camera_imu.translation.map(|v| v * unit.in_meters());
+        Ok(calibration.with_camera_imu(camera_imu))
+    }
+
+    /// Gets the pose of the IMU in the left camera frame, which maps IMU
+    /// points into the camera frame. It is expressed in the coordinate
+    /// system and unit of the camera.
+    pub fn camera_imu_transform(&mut self) -> Transform {
+        let id = self.id();
+        let mut translation = Vector3 {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        };
+        let mut rotation = Quaternion {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            w: 1.0,
+        };
+
+        unsafe {
+            sys::sl_get_camera_imu_transform(id, &mut translation, &mut rotation);
+        }
+        Transform::from_quaternion(rotation, translation)
+    }
+
+    pub fn grab(&mut self, runtime: RuntimeParams) -> Result<GrabHandle<'_, I, R, S>> {
+        let mut runtime = runtime.to_raw()?;
+        self.grab_raw(&mut runtime)?;
+        Ok(GrabHandle { camera: self })
+    }
+
+    pub(super) fn grab_raw(&mut self, runtime: &mut RuntimeParameters) -> Result<()> {
+        let id = self.id();
+        let code = unsafe { sys::sl_grab(id, runtime as *mut _) };
+        code_to_result(code).map_err(|err| err.with_context(self.inner.context(Operation::Grab)))
+    }
+
+    /// Stops streaming and recording if active, then closes the camera.
+    ///
+    /// Unlike dropping the camera, the failure of each step is reported.
+    pub fn try_close(mut self) -> Result<(), CloseError> {
+        self.inner.shutdown()
+    }
+
+    pub(super) fn into_state<I2, R2, S2>(self) -> Camera<I2, R2, S2> {
+        Camera {
+            inner: self.inner,
+            _phantom: PhantomData,
+        }
+    }
 }
 
 impl<I> Camera<I, rs::Inactive, ss::Inactive> {
-    pub(crate) fn new(id: c_int) -> Self {
+    pub(crate) fn new(slot: CameraSlot, input_type: InputType) -> Self {
         Self {
             inner: Inner {
-                id,
+                slot,
+                input_type,
+                serial_number: None,
+                input_path: None,
+                input_addr: None,
+                coordinate_unit: Unit::SL_UNIT_MILLIMETER,
+                coordinate_system: CoordinateSystem::SL_COORDINATE_SYSTEM_IMAGE,
                 recording_state: RecordingState::Inactive,
                 streaming_state: StreamingState::Inactive,
+                closed: false,
             },
             _phantom: PhantomData,
         }
     }
 
-    pub fn close(mut self) {
-        unsafe {
-            let id = self.id();
-            sys::sl_close_camera(id);
-            self.inner.recording_state = RecordingState::Closed;
-            self.inner.streaming_state = StreamingState::Closed;
-        }
+    pub fn close(self) {
+        let _ = self.try_close();
     }
 }
 
@@ -95,84 +203,27 @@ impl<I, S> Camera<I, rs::Inactive, S> {
     where
         P: AsRef<Path>,
     {
-        let output_file = osstr_to_cstr(output_file.as_ref());
-        let id = self.id();
-
-        let code = unsafe {
-            sys::sl_enable_recording(
-                id,
-                output_file.as_ptr(),
-                params.compression_mode,
-                params.bitrate as c_uint,
-                params.target_framerate as c_int,
-                params.transcode_streaming_input,
-            )
-        };
-        code_to_result(code as u32)?;
-
-        Ok(Camera {
-            inner: Inner {
-                recording_state: RecordingState::Recording,
-                ..self.inner
-            },
-            _phantom: PhantomData,
-        })
+        self.inner.enable_recording(output_file.as_ref(), params)?;
+        Ok(self.into_state())
     }
 }
 
 impl<I, S> Camera<I, rs::Recording, S> {
     pub fn disable_recording(mut self) -> Camera<I, rs::Inactive, S> {
-        let id = self.id();
-        unsafe {
-            sys::sl_disable_recording(id);
-        }
-
-        Camera {
-            inner: Inner {
-                recording_state: RecordingState::Inactive,
-                ..self.inner
-            },
-            _phantom: PhantomData,
-        }
+        self.inner.disable_recording();
+        self.into_state()
     }
 
     pub fn pause_recording(mut self) -> Camera<I, rs::Paused, S> {
-        let id = self.id();
-        unsafe {
-            sys::sl_pause_recording(id, true);
-        }
-
-        Camera {
-            inner: Inner {
-                recording_state: RecordingState::Paused,
-                ..self.inner
-            },
-            _phantom: PhantomData,
-        }
-    }
-
-    pub fn grab(&mut self, mut runtime: RuntimeParameters) -> Result<GrabHandle<'_, I, S>> {
-        let id = self.id();
-        let code = unsafe { sys::sl_grab(id, &mut runtime as *mut _) };
-        code_to_result(code as u32)?;
-        Ok(GrabHandle { camera: self })
+        self.inner.pause_recording(true);
+        self.into_state()
     }
 }
 
 impl<I, S> Camera<I, rs::Paused, S> {
     pub fn resume_recording(mut self) -> Camera<I, rs::Recording, S> {
-        let id = self.id();
-        unsafe {
-            sys::sl_pause_recording(id, false);
-        }
-
-        Camera {
-            inner: Inner {
-                recording_state: RecordingState::Recording,
-                ..self.inner
-            },
-            _phantom: PhantomData,
-        }
+        self.inner.pause_recording(false);
+        self.into_state()
     }
 }
 
@@ -181,55 +232,15 @@ impl<I, R> Camera<I, R, ss::Inactive> {
         mut self,
         params: StreamingParameters,
     ) -> Result<Camera<I, R, ss::Streaming>> {
-        let id = self.id();
-        let StreamingParameters {
-            codec,
-            bitrate,
-            port,
-            gop_size,
-            adaptative_bitrate,
-            chunk_size,
-            target_framerate,
-        } = params;
-
-        let code = unsafe {
-            sys::sl_enable_streaming(
-                id,
-                codec,
-                bitrate as c_uint,
-                port as c_ushort,
-                gop_size.map(|size| size as c_int).unwrap_or(-1),
-                adaptative_bitrate as c_int,
-                chunk_size as c_int,
-                target_framerate.map(|fps| fps as c_int).unwrap_or(0),
-            )
-        };
-        code_to_result(code as u32)?;
-
-        Ok(Camera {
-            inner: Inner {
-                streaming_state: StreamingState::Streaming,
-                ..self.inner
-            },
-            _phantom: PhantomData,
-        })
+        self.inner.enable_streaming(params)?;
+        Ok(self.into_state())
     }
 }
 
 impl<I, R> Camera<I, R, ss::Streaming> {
     pub fn disable_streaming(mut self) -> Camera<I, R, ss::Inactive> {
-        let id = self.id();
-        unsafe {
-            sys::sl_disable_streaming(id);
-        }
-
-        Camera {
-            inner: Inner {
-                streaming_state: StreamingState::Inactive,
-                ..self.inner
-            },
-            _phantom: PhantomData,
-        }
+        self.inner.disable_streaming();
+        self.into_state()
     }
 }
 
@@ -256,60 +267,145 @@ pub use grab::*;
 mod grab {
     use super::*;
 
-    pub struct GrabHandle<'a, I, S> {
-        pub(super) camera: &'a mut Camera<I, rs::Recording, S>,
+    pub struct GrabHandle<'a, I, R, S> {
+        pub(in crate::camera) camera: &'a mut Camera<I, R, S>,
     }
 
-    impl<'a, I, S> GrabHandle<'a, I, S> {
-        pub fn retrieve_image<B>(
+    impl<'a, I, R, S> GrabHandle<'a, I, R, S> {
+        /// Retrieves the image of the view into `mat`.
+        ///
+        /// The resolution is that of one camera image and defaults to the
+        /// camera resolution. It must not exceed the camera resolution. The
+        /// image of [View::SL_VIEW_SIDE_BY_SIDE] is twice as wide. The Mat
+        /// is reallocated if its size differs, and its pixel format must
+        /// match the view, i.e. [U8C1](crate::pixel_format::U8C1) for gray
+        /// views and [U8C4](crate::pixel_format::U8C4) otherwise.
+        pub fn retrieve_image<T, W>(
             &mut self,
-            type_: View,
-            mem: Mem,
-            wh: (usize, usize),
-            mut buffer: B,
+            view: View,
+            resolution: W,
+            mat: &mut Mat<T>,
         ) -> Result<()>
         where
-            B: AsMut<[c_int]>,
+            T: PixelFormat,
+            W: Into<Option<(usize, usize)>>,
         {
-            let id = self.camera.id();
-            let (width, height) = wh;
-            let buffer = buffer.as_mut();
             ensure!(
-                buffer.len() == width * height,
-                "expect buffer size to be {} for {}x{}, but get {}",
-                width * height,
-                width,
-                height,
-                buffer.len()
+                T::MAT_TYPE == view.mat_type(),
+                "the Mat with {} channels does not match the pixel format of view {:?}",
+                T::CHANNELS,
+                view
             );
 
+            let (width, height) = self.check_resolution(resolution.into())?;
+            let id = self.camera.id();
             let code = unsafe {
                 sys::sl_retrieve_image(
                     id,
-                    buffer.as_mut_ptr(),
-                    type_,
-                    mem,
+                    mat.as_raw(),
+                    view,
+                    Mem::SL_MEM_CPU,
                     width as c_int,
                     height as c_int,
                 )
             };
-            code_to_result(code as u32)
+            mat.sync_layout();
+            code_to_result(code).map_err(|err| {
+                err.with_context(self.camera.inner.context(Operation::RetrieveImage))
+            })?;
+
+            mat.check_format()?;
+            let expect = (width * view.width_factor(), height);
+            ensure!(
+                mat.resolution() == expect,
+                "expect retrieved image of size {}x{}, but get {}x{}",
+                expect.0,
+                expect.1,
+                mat.width(),
+                mat.height()
+            );
+            Ok(())
         }
 
-        pub fn retrieve_image_to_vec<R>(
-            &mut self,
-            type_: View,
-            mem: Mem,
-            wh: R,
-        ) -> Result<Vec<c_int>>
+        /// Retrieves the image of the view into a new Mat.
+        ///
+        /// See [retrieve_image](Self::retrieve_image) for the resolution
+        /// and the pixel format.
+        pub fn retrieve_image_to_mat<T, W>(&mut self, view: View, resolution: W) -> Result<Mat<T>>
         where
-            R: Into<Option<(usize, usize)>>,
+            T: PixelFormat,
+            W: Into<Option<(usize, usize)>>,
         {
-            let wh = wh.into().unwrap_or_else(|| self.camera.resolution());
-            let (width, height) = wh;
-            let mut buffer: Vec<c_int> = vec![0; width * height];
-            self.retrieve_image(type_, mem, (width, height), &mut buffer)?;
-            Ok(buffer)
+            let mut mat = Mat::new_empty()?;
+            self.retrieve_image(view, resolution, &mut mat)?;
+            Ok(mat)
+        }
+
+        /// Retrieves the measure into a new buffer.
+        ///
+        /// The buffer type must match the measure, e.g. [DepthMap](crate::DepthMap)
+        /// for [Measure::SL_MEASURE_DEPTH]. The resolution defaults to the
+        /// camera resolution and must not exceed it.
+        pub fn retrieve_measure<M, W>(&mut self, measure: Measure, resolution: W) -> Result<M>
+        where
+            M: MeasureBuffer,
+            W: Into<Option<(usize, usize)>>,
+        {
+            ensure!(
+                M::accepts(measure),
+                "the measure {:?} cannot be stored in {}",
+                measure,
+                std::any::type_name::<M>()
+            );
+
+            let (width, height) = self.check_resolution(resolution.into())?;
+            let mut mat = Mat::<M::Format>::new_empty()?;
+            let id = self.camera.id();
+            let code = unsafe {
+                sys::sl_retrieve_measure(
+                    id,
+                    mat.as_raw(),
+                    measure,
+                    Mem::SL_MEM_CPU,
+                    width as c_int,
+                    height as c_int,
+                )
+            };
+            mat.sync_layout();
+            code_to_result(code).map_err(|err| {
+                err.with_context(self.camera.inner.context(Operation::RetrieveMeasure))
+            })?;
+
+            mat.check_format()?;
+            ensure!(
+                mat.resolution() == (width, height),
+                "expect retrieved measure of size {}x{}, but get {}x{}",
+                width,
+                height,
+                mat.width(),
+                mat.height()
+            );
+            Ok(M::from_mat(mat, measure, self.camera.coordinate_unit()))
+        }
+
+        /// Defaults the requested resolution to the camera resolution and
+        /// checks that it does not exceed it.
+        fn check_resolution(
+            &mut self,
+            resolution: Option<(usize, usize)>,
+        ) -> Result<(usize, usize)> {
+            let camera_resolution = self.camera.resolution();
+            let (width, height) = resolution.unwrap_or(camera_resolution);
+            ensure!(
+                (1..=camera_resolution.0).contains(&width)
+                    && (1..=camera_resolution.1).contains(&height),
+                "requested resolution {}x{} must be non-empty and within the camera resolution {}x{}",
+                width,
+                height,
+                camera_resolution.0,
+                camera_resolution.1
+            );
+            Ok((width, height))
         }
 
         pub fn image_timestamp(&mut self) -> c_ulonglong {
@@ -327,65 +423,210 @@ mod grab {
             P: AsRef<Path>,
         {
             let id = self.camera.id();
-            let output_file = osstr_to_cstr(output_file.as_ref());
+            let output_path = output_file.as_ref();
+            let output_file = osstr_to_cstr(output_path);
 
             let code = unsafe { sys::sl_save_current_image(id, view, output_file.as_ptr()) };
-            code_to_result(code as u32)
+            code_to_result(code).map_err(|err| {
+                err.with_context(ErrorContext {
+                    path: Some(output_path.to_owned()),
+                    ..self.camera.inner.context(Operation::SaveImage)
+                })
+            })
         }
     }
 }
 
-use inner::*;
+pub(super) use inner::*;
 mod inner {
     use super::*;
 
     #[derive(Debug)]
     pub struct Inner {
-        pub id: c_int,
+        pub slot: CameraSlot,
+        pub input_type: InputType,
+        pub serial_number: Option<c_int>,
+        pub input_path: Option<PathBuf>,
+        pub input_addr: Option<SocketAddr>,
+        pub coordinate_unit: Unit,
+        pub coordinate_system: CoordinateSystem,
         pub recording_state: RecordingState,
         pub streaming_state: StreamingState,
+        pub closed: bool,
     }
 
-    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-    pub enum RecordingState {
-        Inactive,
-        Recording,
-        Paused,
-        Closed,
-    }
+    impl Inner {
+        /// Describes the camera for an error of the operation.
+        pub fn context(&self, operation: Operation) -> ErrorContext {
+            let serial_number = self.serial_number.or_else(|| {
+                let sn = unsafe { sys::sl_get_zed_serial(self.slot.id()) };
+                (sn > 0).then_some(sn)
+            });
+
+            ErrorContext {
+                camera_id: Some(self.slot.id()),
+                serial_number,
+                path: self.input_path.clone(),
+                addr: self.input_addr,
+                ..ErrorContext::new(operation)
+            }
+        }
 
-    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-    pub enum StreamingState {
-        Inactive,
-        Streaming,
-        Closed,
-    }
+        pub fn enable_recording(
+            &mut self,
+            output_path: &Path,
+            params: RecordingParams,
+        ) -> Result<()> {
+            let output_file = osstr_to_cstr(output_path);
+            let id = self.slot.id();
 
-    impl Drop for Inner {
-        fn drop(&mut self) {
-            let id = self.id;
-
-            match self.recording_state {
-                RecordingState::Inactive => {}
-                RecordingState::Recording => unsafe {
-                    sys::sl_disable_recording(id);
-                    sys::sl_close_camera(id);
-                },
-                RecordingState::Paused => unsafe {
-                    sys::sl_disable_recording(id);
-                    sys::sl_close_camera(id);
-                },
-                RecordingState::Closed => {}
+            let code = unsafe {
+                sys::sl_enable_recording(
+                    id,
+                    output_file.as_ptr(),
+                    params.compression_mode,
+                    params.bitrate as c_uint,
+                    params.target_framerate as c_int,
+                    params.transcode_streaming_input,
+                )
+            };
+            code_to_result(code).map_err(|err| {
+                err.with_context(ErrorContext {
+                    path: Some(output_path.to_owned()),
+                    ..self.context(Operation::EnableRecording)
+                })
+            })?;
+
+            self.recording_state = RecordingState::Recording;
+            Ok(())
+        }
+
+        pub fn disable_recording(&mut self) {
+            unsafe {
+                sys::sl_disable_recording(self.slot.id());
             }
+            self.recording_state = RecordingState::Inactive;
+        }
 
-            match self.streaming_state {
-                StreamingState::Inactive => {}
-                StreamingState::Streaming => unsafe {
+        pub fn pause_recording(&mut self, pause: bool) {
+            unsafe {
+                sys::sl_pause_recording(self.slot.id(), pause);
+            }
+            self.recording_state = if pause {
+                RecordingState::Paused
+            } else {
+                RecordingState::Recording
+            };
+        }
+
+        pub fn enable_streaming(&mut self, params: StreamingParameters) -> Result<()> {
+            let id = self.slot.id();
+            let StreamingParameters {
+                codec,
+                bitrate,
+                port,
+                gop_size,
+                adaptative_bitrate,
+                chunk_size,
+                target_framerate,
+            } = params;
+
+            let code = unsafe {
+                sys::sl_enable_streaming(
+                    id,
+                    codec,
+                    bitrate as c_uint,
+                    port as c_ushort,
+                    gop_size.map(|size| size as c_int).unwrap_or(-1),
+                    adaptative_bitrate as c_int,
+                    chunk_size as c_int,
+                    target_framerate.map(|fps| fps as c_int).unwrap_or(0),
+                )
+            };
+            code_to_result(code).map_err(|err| {
+                err.with_context(ErrorContext {
+                    addr: Some(SocketAddr::from((Ipv4Addr::UNSPECIFIED, port))),
+                    ..self.context(Operation::EnableStreaming)
+                })
+            })?;
+
+            self.streaming_state = StreamingState::Streaming;
+            Ok(())
+        }
+
+        pub fn disable_streaming(&mut self) {
+            unsafe {
+                sys::sl_disable_streaming(self.slot.id());
+            }
+            self.streaming_state = StreamingState::Inactive;
+        }
+
+        /// Stops streaming, stops recording and closes the camera.
+        ///
+        /// Every step runs even if an earlier one fails. Calling it again
+        /// after the camera is closed is a no-op.
+        pub fn shutdown(&mut self) -> Result<(), CloseError> {
+            let id = self.slot.id();
+            let mut failures = vec![];
+
+            if self.closed {
+                return Ok(());
+            }
+
+            if self.streaming_state == StreamingState::Streaming {
+                let enabled = unsafe {
                     sys::sl_disable_streaming(id);
-                    sys::sl_close_camera(id);
-                },
-                StreamingState::Closed => {}
+                    sys::sl_is_streaming_enabled(id)
+                };
+                if enabled != 0 {
+                    failures.push((
+                        CloseStep::DisableStreaming,
+                        StateError::Streaming {
+                            expect: StreamingState::Inactive,
+                            found: StreamingState::Streaming,
+                        }
+                        .into(),
+                    ));
+                }
+            }
+            self.streaming_state = StreamingState::Inactive;
+
+            // The C API reports neither the recording status nor whether
+            // the SVO file is finalized, so this step cannot be verified.
+            // It is only known to fail if the camera is already closed,
+            // since the file can only be finalized while it is opened.
+            if self.recording_state != RecordingState::Inactive {
+                if unsafe { sys::sl_is_opened(id) } {
+                    unsafe { sys::sl_disable_recording(id) };
+                } else {
+                    failures.push((
+                        CloseStep::DisableRecording,
+                        Error::Desc("the camera is closed before the SVO file is finalized".into()),
+                    ));
+                }
             }
+            self.recording_state = RecordingState::Inactive;
+
+            let opened = unsafe {
+                sys::sl_close_camera(id);
+                sys::sl_is_opened(id)
+            };
+            self.closed = true;
+            if opened {
+                failures.push((CloseStep::CloseCamera, StateError::Opened.into()));
+            }
+
+            if failures.is_empty() {
+                Ok(())
+            } else {
+                Err(CloseError { failures })
+            }
+        }
+    }
+
+    impl Drop for Inner {
+        fn drop(&mut self) {
+            let _ = self.shutdown();
         }
     }
 }
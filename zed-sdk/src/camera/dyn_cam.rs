@@ -0,0 +1,373 @@
+use super::{input_source as is, recording_state as rs, streaming_state as ss, Camera};
+use crate::{
+    common::*, error::StateError, InputType, RecordingParams, Result, StreamingParameters,
+};
+
+/// A camera whose input source, recording and streaming states are checked
+/// at runtime.
+///
+/// It is convenient to keep in a struct field while the states are toggled
+/// at runtime. Invalid transitions return [StateError] instead of failing
+/// to compile.
+pub type DynCamera = Camera<is::Dynamic, rs::Dynamic, ss::Dynamic>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RecordingState {
+    Inactive,
+    Recording,
+    Paused,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StreamingState {
+    Inactive,
+    Streaming,
+}
+
+/// The runtime states of a camera, with the checks of the transitions
+/// kept apart from the SDK calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct States {
+    input_type: InputType,
+    recording: RecordingState,
+    streaming: StreamingState,
+}
+
+impl States {
+    /// Checks that the marker types agree with the states. Dynamic markers
+    /// agree with any state.
+    fn check_markers<I, R, S>(&self) -> Result<(), StateError>
+    where
+        I: is::InputSource,
+        R: rs::RecordingState,
+        S: ss::StreamingState,
+    {
+        if let Some(expect) = I::INPUT_TYPE {
+            self.expect_input_type(expect)?;
+        }
+        if let Some(expect) = R::STATE {
+            self.expect_recording(expect)?;
+        }
+        if let Some(expect) = S::STATE {
+            self.expect_streaming(expect)?;
+        }
+        Ok(())
+    }
+
+    fn expect_input_type(&self, expect: InputType) -> Result<(), StateError> {
+        let found = self.input_type;
+        if expect != found {
+            return Err(StateError::Input { expect, found });
+        }
+        Ok(())
+    }
+
+    fn expect_recording(&self, expect: RecordingState) -> Result<(), StateError> {
+        let found = self.recording;
+        if expect != found {
+            return Err(StateError::Recording { expect, found });
+        }
+        Ok(())
+    }
+
+    fn expect_streaming(&self, expect: StreamingState) -> Result<(), StateError> {
+        let found = self.streaming;
+        if expect != found {
+            return Err(StateError::Streaming { expect, found });
+        }
+        Ok(())
+    }
+
+    /// Checks that recording can go from `from` to `to`, and returns the
+    /// states after the transition.
+    fn recording_transition(
+        self,
+        from: RecordingState,
+        to: RecordingState,
+    ) -> Result<Self, StateError> {
+        self.expect_recording(from)?;
+        Ok(Self {
+            recording: to,
+            ..self
+        })
+    }
+
+    fn streaming_transition(
+        self,
+        from: StreamingState,
+        to: StreamingState,
+    ) -> Result<Self, StateError> {
+        self.expect_streaming(from)?;
+        Ok(Self {
+            streaming: to,
+            ..self
+        })
+    }
+
+    fn enable_recording(self) -> Result<Self, StateError> {
+        self.recording_transition(RecordingState::Inactive, RecordingState::Recording)
+    }
+
+    fn disable_recording(self) -> Result<Self, StateError> {
+        self.recording_transition(RecordingState::Recording, RecordingState::Inactive)
+    }
+
+    fn pause_recording(self) -> Result<Self, StateError> {
+        self.recording_transition(RecordingState::Recording, RecordingState::Paused)
+    }
+
+    fn resume_recording(self) -> Result<Self, StateError> {
+        self.recording_transition(RecordingState::Paused, RecordingState::Recording)
+    }
+
+    fn enable_streaming(self) -> Result<Self, StateError> {
+        self.streaming_transition(StreamingState::Inactive, StreamingState::Streaming)
+    }
+
+    fn disable_streaming(self) -> Result<Self, StateError> {
+        self.streaming_transition(StreamingState::Streaming, StreamingState::Inactive)
+    }
+}
+
+impl<I, R, S> Camera<I, R, S> {
+    pub fn input_type(&self) -> InputType {
+        self.inner.input_type
+    }
+
+    pub fn recording_state(&self) -> RecordingState {
+        self.inner.recording_state
+    }
+
+    pub fn streaming_state(&self) -> StreamingState {
+        self.inner.streaming_state
+    }
+
+    /// Forgets the compile-time states and checks them at runtime instead.
+    pub fn into_dyn(self) -> DynCamera {
+        self.into_state()
+    }
+
+    /// Converts to a camera with the given marker types.
+    ///
+    /// The camera is given back along with the error if the marker types
+    /// do not agree with the runtime states.
+    pub fn try_into_typed<I2, R2, S2>(self) -> Result<Camera<I2, R2, S2>, (Self, StateError)>
+    where
+        I2: is::InputSource,
+        R2: rs::RecordingState,
+        S2: ss::StreamingState,
+    {
+        match self.states().check_markers::<I2, R2, S2>() {
+            Ok(()) => Ok(self.into_state()),
+            Err(err) => Err((self, err)),
+        }
+    }
+
+    fn states(&self) -> States {
+        States {
+            input_type: self.input_type(),
+            recording: self.recording_state(),
+            streaming: self.streaming_state(),
+        }
+    }
+
+    fn expect_input_type(&self, expect: InputType) -> Result<(), StateError> {
+        self.states().expect_input_type(expect)
+    }
+}
+
+impl<I, S> Camera<I, rs::Dynamic, S> {
+    pub fn enable_recording<P>(&mut self, output_file: P, params: RecordingParams) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let next = self.states().enable_recording()?;
+        self.inner.enable_recording(output_file.as_ref(), params)?;
+        debug_assert_eq!(self.states(), next);
+        Ok(())
+    }
+
+    pub fn disable_recording(&mut self) -> Result<()> {
+        let next = self.states().disable_recording()?;
+        self.inner.disable_recording();
+        debug_assert_eq!(self.states(), next);
+        Ok(())
+    }
+
+    pub fn pause_recording(&mut self) -> Result<()> {
+        let next = self.states().pause_recording()?;
+        self.inner.pause_recording(true);
+        debug_assert_eq!(self.states(), next);
+        Ok(())
+    }
+
+    pub fn resume_recording(&mut self) -> Result<()> {
+        let next = self.states().resume_recording()?;
+        self.inner.pause_recording(false);
+        debug_assert_eq!(self.states(), next);
+        Ok(())
+    }
+}
+
+impl<I, R> Camera<I, R, ss::Dynamic> {
+    pub fn enable_streaming(&mut self, params: StreamingParameters) -> Result<()> {
+        let next = self.states().enable_streaming()?;
+        self.inner.enable_streaming(params)?;
+        debug_assert_eq!(self.states(), next);
+        Ok(())
+    }
+
+    pub fn disable_streaming(&mut self) -> Result<()> {
+        let next = self.states().disable_streaming()?;
+        self.inner.disable_streaming();
+        debug_assert_eq!(self.states(), next);
+        Ok(())
+    }
+}
+
+impl<R, S> Camera<is::Dynamic, R, S> {
+    pub fn svo_position(&mut self) -> Result<usize> {
+        self.expect_input_type(InputType::SL_INPUT_TYPE_SVO)?;
+        let id = self.id();
+        Ok(unsafe { sys::sl_get_svo_position(id) as usize })
+    }
+
+    pub fn set_svo_position(&mut self, pos: usize) -> Result<()> {
+        self.expect_input_type(InputType::SL_INPUT_TYPE_SVO)?;
+        let id = self.id();
+        unsafe {
+            sys::sl_set_svo_position(id, pos as c_int);
+        }
+        Ok(())
+    }
+
+    pub fn num_frames(&mut self) -> Result<usize> {
+        self.expect_input_type(InputType::SL_INPUT_TYPE_SVO)?;
+        let id = self.id();
+        Ok(unsafe { sys::sl_get_svo_number_of_frames(id) as usize })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USB: InputType = InputType::SL_INPUT_TYPE_USB;
+    const SVO: InputType = InputType::SL_INPUT_TYPE_SVO;
+
+    fn opened(input_type: InputType) -> States {
+        States {
+            input_type,
+            recording: RecordingState::Inactive,
+            streaming: StreamingState::Inactive,
+        }
+    }
+
+    #[test]
+    fn reject_pause_without_recording() {
+        assert_eq!(
+            opened(USB).pause_recording(),
+            Err(StateError::Recording {
+                expect: RecordingState::Recording,
+                found: RecordingState::Inactive,
+            })
+        );
+        assert_eq!(
+            opened(USB).resume_recording(),
+            Err(StateError::Recording {
+                expect: RecordingState::Paused,
+                found: RecordingState::Inactive,
+            })
+        );
+    }
+
+    #[test]
+    fn reject_enabling_twice() {
+        let recording = opened(USB).enable_recording().unwrap();
+        assert_eq!(
+            recording.enable_recording(),
+            Err(StateError::Recording {
+                expect: RecordingState::Inactive,
+                found: RecordingState::Recording,
+            })
+        );
+
+        let streaming = opened(USB).enable_streaming().unwrap();
+        assert_eq!(
+            streaming.enable_streaming(),
+            Err(StateError::Streaming {
+                expect: StreamingState::Inactive,
+                found: StreamingState::Streaming,
+            })
+        );
+    }
+
+    #[test]
+    fn follow_recording_cycle() {
+        let states = opened(USB)
+            .enable_recording()
+            .and_then(States::pause_recording)
+            .and_then(States::resume_recording)
+            .and_then(States::enable_streaming)
+            .unwrap();
+        assert_eq!(states.recording, RecordingState::Recording);
+        assert_eq!(states.streaming, StreamingState::Streaming);
+
+        let states = states
+            .disable_recording()
+            .and_then(States::disable_streaming)
+            .unwrap();
+        assert_eq!(states, opened(USB));
+    }
+
+    #[test]
+    fn reject_wrong_markers() {
+        let states = opened(SVO).enable_recording().unwrap();
+
+        assert_eq!(
+            states.check_markers::<is::USB, rs::Dynamic, ss::Dynamic>(),
+            Err(StateError::Input {
+                expect: USB,
+                found: SVO
+            })
+        );
+        assert_eq!(
+            states.check_markers::<is::SVO, rs::Paused, ss::Dynamic>(),
+            Err(StateError::Recording {
+                expect: RecordingState::Paused,
+                found: RecordingState::Recording,
+            })
+        );
+        assert_eq!(
+            states.check_markers::<is::Dynamic, rs::Dynamic, ss::Streaming>(),
+            Err(StateError::Streaming {
+                expect: StreamingState::Streaming,
+                found: StreamingState::Inactive,
+            })
+        );
+    }
+
+    #[test]
+    fn round_trip_through_dynamic_markers() {
+        // The states of a `Camera<SVO, Paused, Streaming>`, which are kept
+        // as is by `into_dyn`.
+        let states = opened(SVO)
+            .enable_recording()
+            .and_then(States::pause_recording)
+            .and_then(States::enable_streaming)
+            .unwrap();
+
+        assert_eq!(
+            states.check_markers::<is::Dynamic, rs::Dynamic, ss::Dynamic>(),
+            Ok(())
+        );
+        assert_eq!(
+            states.check_markers::<is::SVO, rs::Paused, ss::Streaming>(),
+            Ok(())
+        );
+        assert_eq!(
+            states.check_markers::<is::SVO, rs::Dynamic, ss::Streaming>(),
+            Ok(())
+        );
+    }
+}
@@ -1,5 +1,29 @@
-use crate::utils::declare_marker;
+use crate::{utils::declare_marker, InputType};
 
 declare_marker!(pub USB);
 declare_marker!(pub SVO);
 declare_marker!(pub Stream);
+declare_marker!(pub Dynamic);
+
+/// Marker types of the camera input source.
+pub trait InputSource {
+    /// The input type encoded by the marker, or `None` if it is checked
+    /// at runtime.
+    const INPUT_TYPE: Option<InputType>;
+}
+
+impl InputSource for USB {
+    const INPUT_TYPE: Option<InputType> = Some(InputType::SL_INPUT_TYPE_USB);
+}
+
+impl InputSource for SVO {
+    const INPUT_TYPE: Option<InputType> = Some(InputType::SL_INPUT_TYPE_SVO);
+}
+
+impl InputSource for Stream {
+    const INPUT_TYPE: Option<InputType> = Some(InputType::SL_INPUT_TYPE_STREAM);
+}
+
+impl InputSource for Dynamic {
+    const INPUT_TYPE: Option<InputType> = None;
+}
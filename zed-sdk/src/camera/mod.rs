@@ -4,6 +4,17 @@ pub use cam::*;
 mod builder;
 pub use builder::*;
 
+mod dyn_cam;
+pub use dyn_cam::*;
+
+mod slot;
+
+mod supervised;
+pub use supervised::*;
+
+mod video_settings;
+pub use video_settings::*;
+
 pub mod input_source;
 pub mod recording_state;
 pub mod streaming_state;
@@ -1,5 +1,29 @@
-use crate::utils::declare_marker;
+use crate::{utils::declare_marker, RecordingState as State};
 
 declare_marker!(pub Inactive);
 declare_marker!(pub Recording);
 declare_marker!(pub Paused);
+declare_marker!(pub Dynamic);
+
+/// Marker types of the camera recording state.
+pub trait RecordingState {
+    /// The state encoded by the marker, or `None` if it is checked at
+    /// runtime.
+    const STATE: Option<State>;
+}
+
+impl RecordingState for Inactive {
+    const STATE: Option<State> = Some(State::Inactive);
+}
+
+impl RecordingState for Recording {
+    const STATE: Option<State> = Some(State::Recording);
+}
+
+impl RecordingState for Paused {
+    const STATE: Option<State> = Some(State::Paused);
+}
+
+impl RecordingState for Dynamic {
+    const STATE: Option<State> = None;
+}
@@ -0,0 +1,55 @@
+use crate::{common::*, error::Error, Result, MAX_CAMERA_PLUGIN};
+use std::sync::Mutex;
+
+/// Occupancy of the camera instance slots in the C API.
+static SLOTS: Mutex<[bool; MAX_CAMERA_PLUGIN]> = Mutex::new([false; MAX_CAMERA_PLUGIN]);
+
+/// An exclusively owned camera instance id.
+///
+/// The slot is created by `sl_create_camera` on acquisition and released
+/// by `sl_unload_instance` on drop.
+#[derive(Debug)]
+pub(crate) struct CameraSlot {
+    id: c_int,
+}
+
+impl CameraSlot {
+    pub fn acquire() -> Result<Self> {
+        let mut slots = SLOTS.lock().unwrap_or_else(|err| err.into_inner());
+
+        let index = slots.iter().position(|used| !used).ok_or_else(|| {
+            Error::Desc(
+                format!(
+                    "no free camera slot, at most {} cameras can be opened at once",
+                    MAX_CAMERA_PLUGIN
+                )
+                .into(),
+            )
+        })?;
+        let id = index as c_int;
+
+        let ok = unsafe { sys::sl_create_camera(id) };
+        if !ok {
+            return Err(Error::Desc(
+                format!("unable to create camera instance {}", id).into(),
+            ));
+        }
+
+        slots[index] = true;
+        Ok(Self { id })
+    }
+
+    pub fn id(&self) -> c_int {
+        self.id
+    }
+}
+
+impl Drop for CameraSlot {
+    fn drop(&mut self) {
+        let mut slots = SLOTS.lock().unwrap_or_else(|err| err.into_inner());
+        unsafe {
+            sys::sl_unload_instance(self.id);
+        }
+        slots[self.id as usize] = false;
+    }
+}
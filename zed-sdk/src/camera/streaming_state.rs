@@ -1,4 +1,24 @@
-use crate::utils::declare_marker;
+use crate::{utils::declare_marker, StreamingState as State};
 
 declare_marker!(pub Inactive);
 declare_marker!(pub Streaming);
+declare_marker!(pub Dynamic);
+
+/// Marker types of the camera streaming state.
+pub trait StreamingState {
+    /// The state encoded by the marker, or `None` if it is checked at
+    /// runtime.
+    const STATE: Option<State>;
+}
+
+impl StreamingState for Inactive {
+    const STATE: Option<State> = Some(State::Inactive);
+}
+
+impl StreamingState for Streaming {
+    const STATE: Option<State> = Some(State::Streaming);
+}
+
+impl StreamingState for Dynamic {
+    const STATE: Option<State> = None;
+}
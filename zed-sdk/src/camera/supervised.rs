@@ -0,0 +1,508 @@
+use super::{input_source as is, recording_state as rs, streaming_state as ss};
+use crate::{
+    common::*, CameraBuilder, DynCamera, Error, GrabHandle, RecordingParams, Result, RuntimeParams,
+    StreamingParameters,
+};
+use std::{sync::mpsc, thread};
+
+/// The camera reopened by a [SupervisedCamera].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SupervisedInput {
+    /// The USB camera with the device index. The index may change when
+    /// cameras are replugged.
+    Usb(c_int),
+    /// The USB camera with the serial number.
+    Serial(c_int),
+    Stream(SocketAddr),
+}
+
+/// The exponential backoff between reconnect attempts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Backoff {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    /// The factor the delay grows by after each failed attempt. Values
+    /// below 1 are treated as 1.
+    pub multiplier: f64,
+    /// Gives up after this number of failed attempts, or never if `None`.
+    pub max_attempts: Option<u32>,
+}
+
+impl Backoff {
+    /// Gets the delay after the `attempt`-th failed attempt, starting from 1.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exp = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let secs = self.initial_delay.as_secs_f64() * self.multiplier.max(1.0).powi(exp);
+        let max_secs = self.max_delay.as_secs_f64();
+
+        if secs.is_finite() && secs < max_secs {
+            Duration::from_secs_f64(secs)
+        } else {
+            self.max_delay
+        }
+    }
+
+    /// Decides what to do after the `attempt`-th reconnect attempt failed
+    /// with `error`. Returns the delay before the next attempt, or `None` to
+    /// give up.
+    fn retry_in(&self, attempt: u32, error: &Error) -> Option<Duration> {
+        let exhausted = matches!(self.max_attempts, Some(max) if attempt >= max);
+        (!exhausted && error.kind().is_retryable()).then(|| self.delay(attempt))
+    }
+}
+
+impl Default for Backoff {
+    /// Retries 10 times over about two minutes, from 0.5 s up to 30 s
+    /// between attempts.
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+            max_attempts: Some(10),
+        }
+    }
+}
+
+/// Reported by a [SupervisedCamera] when it loses and reopens the camera.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SupervisorEvent {
+    /// Grabbing failed with a retryable error and the camera is closed.
+    Disconnected { error: Error },
+    /// Reopening failed and will be retried after `retry_in`.
+    ReconnectFailed {
+        attempt: u32,
+        error: Error,
+        retry_in: Duration,
+    },
+    /// The camera is reopened. Recording continues in `recording_file` if
+    /// it was active.
+    Reconnected {
+        attempts: u32,
+        recording_file: Option<PathBuf>,
+    },
+    /// The maximum number of attempts is reached, or reopening failed with
+    /// an error that is not retryable.
+    GaveUp { attempts: u32, error: Error },
+}
+
+type EventCallback<'a> = Box<dyn FnMut(&SupervisorEvent) + Send + 'a>;
+
+#[derive(Debug)]
+struct RecordingSession {
+    output_file: PathBuf,
+    params: RecordingParams,
+    paused: bool,
+    segment: u32,
+    current_file: PathBuf,
+}
+
+/// A camera that is reopened when grabbing fails with a retryable error.
+///
+/// The camera is reopened with the same [CameraBuilder] configuration, and
+/// recording and streaming are enabled again if they were active. Since the
+/// SDK overwrites the recording file, recording continues in a new segment
+/// `<stem>_<n>.<ext>` next to the original output file. Recording and
+/// streaming must be changed through the supervisor to be restored.
+pub struct SupervisedCamera<'a> {
+    builder: CameraBuilder<'a>,
+    input: SupervisedInput,
+    backoff: Backoff,
+    camera: Option<DynCamera>,
+    recording: Option<RecordingSession>,
+    streaming: Option<StreamingParameters>,
+    on_event: Option<EventCallback<'a>>,
+}
+
+impl<'a> SupervisedCamera<'a> {
+    /// Opens the camera.
+    pub fn new(builder: CameraBuilder<'a>, input: SupervisedInput) -> Result<Self> {
+        let camera = open(builder.clone(), input)?;
+
+        Ok(Self {
+            builder,
+            input,
+            backoff: Backoff::default(),
+            camera: Some(camera),
+            recording: None,
+            streaming: None,
+            on_event: None,
+        })
+    }
+
+    pub fn backoff(self, backoff: Backoff) -> Self {
+        Self { backoff, ..self }
+    }
+
+    /// Calls the callback on each [SupervisorEvent].
+    ///
+    /// It replaces the callback or the channel set before.
+    pub fn on_event<F>(self, callback: F) -> Self
+    where
+        F: FnMut(&SupervisorEvent) + Send + 'a,
+    {
+        Self {
+            on_event: Some(Box::new(callback)),
+            ..self
+        }
+    }
+
+    /// Sends each [SupervisorEvent] to the returned receiver.
+    ///
+    /// It replaces the callback or the channel set before.
+    pub fn event_channel(&mut self) -> mpsc::Receiver<SupervisorEvent> {
+        let (tx, rx) = mpsc::channel();
+        self.on_event = Some(Box::new(move |event: &SupervisorEvent| {
+            let _ = tx.send(event.clone());
+        }));
+        rx
+    }
+
+    /// Gets the camera, or `None` if reconnecting gave up.
+    ///
+    /// Recording and streaming changed through the returned camera are not
+    /// restored on reconnect.
+    pub fn camera(&mut self) -> Option<&mut DynCamera> {
+        self.camera.as_mut()
+    }
+
+    pub fn into_camera(self) -> Option<DynCamera> {
+        self.camera
+    }
+
+    /// Gets the file being recorded to, which changes on each reconnect.
+    pub fn recording_file(&self) -> Option<&Path> {
+        self.recording
+            .as_ref()
+            .map(|session| session.current_file.as_path())
+    }
+
+    /// Grabs a frame, and reopens the camera once if it fails with a
+    /// retryable error.
+    ///
+    /// If the camera is closed because an earlier reconnect gave up, it is
+    /// reopened first.
+    ///
+    /// It blocks while reconnecting, which takes up to the sum of the
+    /// [Backoff] delays, or forever if `max_attempts` is `None`.
+    pub fn grab(
+        &mut self,
+        runtime: RuntimeParams,
+    ) -> Result<GrabHandle<'_, is::Dynamic, rs::Dynamic, ss::Dynamic>> {
+        let mut runtime = runtime.to_raw()?;
+
+        let disconnected = match &mut self.camera {
+            Some(camera) => match camera.grab_raw(&mut runtime) {
+                Ok(()) => false,
+                Err(error) if error.kind().is_retryable() => {
+                    self.emit(SupervisorEvent::Disconnected { error });
+                    true
+                }
+                Err(error) => return Err(error),
+            },
+            None => true,
+        };
+
+        let camera = if disconnected {
+            let camera = self.reconnect()?;
+            camera.grab_raw(&mut runtime)?;
+            camera
+        } else {
+            self.camera.as_mut().unwrap()
+        };
+
+        Ok(GrabHandle { camera })
+    }
+
+    /// Closes and reopens the camera, waiting with backoff between failed
+    /// attempts. It blocks until the camera is reopened or the attempts are
+    /// exhausted.
+    ///
+    /// Only retryable errors are retried. Other errors, e.g. invalid
+    /// parameters or an unwritable recording file, give up at once.
+    pub fn reconnect(&mut self) -> Result<&mut DynCamera> {
+        if let Some(camera) = self.camera.take() {
+            let _ = camera.try_close();
+        }
+
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+
+            match self.reopen() {
+                Ok(camera) => {
+                    self.emit(SupervisorEvent::Reconnected {
+                        attempts: attempt,
+                        recording_file: self.recording_file().map(Path::to_owned),
+                    });
+                    return Ok(self.camera.insert(camera));
+                }
+                Err(error) => {
+                    let retry_in = match self.backoff.retry_in(attempt, &error) {
+                        Some(retry_in) => retry_in,
+                        None => {
+                            self.emit(SupervisorEvent::GaveUp {
+                                attempts: attempt,
+                                error: error.clone(),
+                            });
+                            return Err(error);
+                        }
+                    };
+
+                    self.emit(SupervisorEvent::ReconnectFailed {
+                        attempt,
+                        error,
+                        retry_in,
+                    });
+                    thread::sleep(retry_in);
+                }
+            }
+        }
+    }
+
+    pub fn enable_recording<P>(&mut self, output_file: P, params: RecordingParams) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let output_file = output_file.as_ref();
+        self.connected()?
+            .enable_recording(output_file, params.clone())?;
+        self.recording = Some(RecordingSession {
+            output_file: output_file.to_owned(),
+            params,
+            paused: false,
+            segment: 0,
+            current_file: output_file.to_owned(),
+        });
+        Ok(())
+    }
+
+    pub fn disable_recording(&mut self) -> Result<()> {
+        self.connected()?.disable_recording()?;
+        self.recording = None;
+        Ok(())
+    }
+
+    pub fn pause_recording(&mut self) -> Result<()> {
+        self.connected()?.pause_recording()?;
+        if let Some(session) = &mut self.recording {
+            session.paused = true;
+        }
+        Ok(())
+    }
+
+    pub fn resume_recording(&mut self) -> Result<()> {
+        self.connected()?.resume_recording()?;
+        if let Some(session) = &mut self.recording {
+            session.paused = false;
+        }
+        Ok(())
+    }
+
+    pub fn enable_streaming(&mut self, params: StreamingParameters) -> Result<()> {
+        self.connected()?.enable_streaming(params.clone())?;
+        self.streaming = Some(params);
+        Ok(())
+    }
+
+    pub fn disable_streaming(&mut self) -> Result<()> {
+        self.connected()?.disable_streaming()?;
+        self.streaming = None;
+        Ok(())
+    }
+
+    fn connected(&mut self) -> Result<&mut DynCamera> {
+        self.camera
+            .as_mut()
+            .ok_or(Error::Desc("the camera is disconnected".into()))
+    }
+
+    /// Opens the camera and restores streaming and recording.
+    ///
+    /// Recording is enabled last so that no segment file is created when an
+    /// earlier step fails, and the segment number is only advanced once the
+    /// camera is fully restored.
+    fn reopen(&mut self) -> Result<DynCamera> {
+        let mut camera = open(self.builder.clone(), self.input)?;
+
+        if let Some(params) = &self.streaming {
+            camera.enable_streaming(params.clone())?;
+        }
+
+        if let Some(session) = &mut self.recording {
+            let segment = session.segment + 1;
+            let file = segment_path(&session.output_file, segment);
+            camera.enable_recording(&file, session.params.clone())?;
+            if session.paused {
+                camera.pause_recording()?;
+            }
+            session.segment = segment;
+            session.current_file = file;
+        }
+
+        Ok(camera)
+    }
+
+    fn emit(&mut self, event: SupervisorEvent) {
+        if let Some(callback) = &mut self.on_event {
+            callback(&event);
+        }
+    }
+}
+
+impl<'a> fmt::Debug for SupervisedCamera<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SupervisedCamera")
+            .field("builder", &self.builder)
+            .field("input", &self.input)
+            .field("backoff", &self.backoff)
+            .field("camera", &self.camera)
+            .field("recording", &self.recording)
+            .field("streaming", &self.streaming)
+            .finish_non_exhaustive()
+    }
+}
+
+fn open(builder: CameraBuilder<'_>, input: SupervisedInput) -> Result<DynCamera> {
+    let camera = match input {
+        SupervisedInput::Usb(id) => builder.open_usb(id)?.into_dyn(),
+        SupervisedInput::Serial(sn) => builder.open_serial(sn)?.into_dyn(),
+        SupervisedInput::Stream(addr) => builder.open_stream(addr)?.into_dyn(),
+    };
+    Ok(camera)
+}
+
+/// Inserts the segment number before the extension, e.g. `out.svo` becomes
+/// `out_1.svo`.
+fn segment_path(path: &Path, segment: u32) -> PathBuf {
+    let mut name = path.file_stem().unwrap_or_default().to_owned();
+    name.push(format!("_{}", segment));
+    if let Some(ext) = path.extension() {
+        name.push(".");
+        name.push(ext);
+    }
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::{ErrorContext, Operation},
+        ErrorCode,
+    };
+
+    fn backoff(multiplier: f64) -> Backoff {
+        Backoff {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+            multiplier,
+            max_attempts: None,
+        }
+    }
+
+    #[test]
+    fn grow_delay() {
+        let backoff = backoff(2.0);
+        assert_eq!(backoff.delay(0), Duration::from_millis(100));
+        assert_eq!(backoff.delay(1), Duration::from_millis(100));
+        assert_eq!(backoff.delay(2), Duration::from_millis(200));
+        assert_eq!(backoff.delay(4), Duration::from_millis(800));
+    }
+
+    #[test]
+    fn clamp_delay_at_max() {
+        let backoff = backoff(2.0);
+        assert_eq!(backoff.delay(5), Duration::from_secs(1));
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(1));
+        assert_eq!(
+            Backoff {
+                multiplier: f64::INFINITY,
+                ..backoff
+            }
+            .delay(2),
+            Duration::from_secs(1)
+        );
+    }
+
+    #[test]
+    fn keep_delay_for_small_multiplier() {
+        for multiplier in [0.5, 0.0, -1.0, f64::NAN] {
+            let backoff = backoff(multiplier);
+            assert_eq!(backoff.delay(1), Duration::from_millis(100));
+            assert_eq!(backoff.delay(10), Duration::from_millis(100));
+        }
+    }
+
+    #[test]
+    fn retry_until_max_attempts() {
+        let backoff = Backoff {
+            max_attempts: Some(3),
+            ..backoff(2.0)
+        };
+        let error = Error::Code(ErrorCode::SL_ERROR_CODE_CAMERA_NOT_DETECTED);
+
+        assert_eq!(
+            backoff.retry_in(1, &error),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(
+            backoff.retry_in(2, &error),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(backoff.retry_in(3, &error), None);
+        assert_eq!(backoff.retry_in(4, &error), None);
+    }
+
+    #[test]
+    fn give_up_by_default() {
+        let backoff = Backoff::default();
+        let error = Error::Code(ErrorCode::SL_ERROR_CODE_CAMERA_NOT_DETECTED);
+        let total: Duration = (1..)
+            .map_while(|attempt| backoff.retry_in(attempt, &error))
+            .sum();
+
+        assert_eq!(backoff.max_attempts, Some(10));
+        assert_eq!(total, Duration::from_millis(121_500));
+    }
+
+    #[test]
+    fn retry_forever_without_max_attempts() {
+        let backoff = backoff(2.0);
+        let error = Error::Code(ErrorCode::SL_ERROR_CODE_LOW_USB_BANDWIDTH)
+            .with_context(ErrorContext::new(Operation::Open));
+
+        assert_eq!(
+            backoff.retry_in(u32::MAX, &error),
+            Some(Duration::from_secs(1))
+        );
+    }
+
+    #[test]
+    fn give_up_on_errors_not_retryable() {
+        let backoff = backoff(2.0);
+
+        for error in [
+            Error::Code(ErrorCode::SL_ERROR_CODE_INVALID_SVO_FILE),
+            Error::Code(ErrorCode::SL_ERROR_CODE_CUDA_ERROR),
+            Error::Unknown(-1),
+            Error::Desc("unable to create the recording file".into()),
+        ] {
+            assert_eq!(backoff.retry_in(1, &error), None, "{:?}", error);
+        }
+    }
+
+    #[test]
+    fn number_segments() {
+        assert_eq!(
+            segment_path(Path::new("/data/out.svo"), 1),
+            Path::new("/data/out_1.svo")
+        );
+        assert_eq!(
+            segment_path(Path::new("rec.v2.svo"), 12),
+            Path::new("rec.v2_12.svo")
+        );
+        assert_eq!(segment_path(Path::new("out"), 3), Path::new("out_3"));
+    }
+}
@@ -0,0 +1,447 @@
+use super::Camera;
+use crate::{
+    common::*,
+    ensure,
+    error::{code_to_result, Operation},
+    Error, Rect, Result, Side, VideoSettings,
+};
+
+/// A percentage within 0 and 100.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Percent(pub u8);
+
+/// A color temperature in Kelvin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Kelvin(pub u16);
+
+/// A video setting along with its value.
+///
+/// The valid ranges follow the SDK documentation and are checked by
+/// [validate](Self::validate) before the value is sent to the camera.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum VideoSetting {
+    /// Brightness within 0 and 8.
+    Brightness(u8),
+    /// Contrast within 0 and 8.
+    Contrast(u8),
+    /// Hue within 0 and 11.
+    Hue(u8),
+    /// Saturation within 0 and 8.
+    Saturation(u8),
+    /// Digital sharpening within 0 and 8.
+    Sharpness(u8),
+    /// ISP gamma within 1 and 9.
+    Gamma(u8),
+    /// Manual gain. It disables the automatic exposure and gain.
+    Gain(Percent),
+    /// Manual exposure. It disables the automatic exposure and gain.
+    Exposure(Percent),
+    AutoExposureGain(bool),
+    /// White balance within 2800K and 6500K with a step of 100K. It
+    /// disables the automatic white balance.
+    WhiteBalance(Kelvin),
+    AutoWhiteBalance(bool),
+    Led(bool),
+}
+
+impl VideoSetting {
+    pub fn kind(&self) -> VideoSettings {
+        match self {
+            VideoSetting::Brightness(_) => VideoSettings::SL_VIDEO_SETTINGS_BRIGHTNESS,
+            VideoSetting::Contrast(_) => VideoSettings::SL_VIDEO_SETTINGS_CONTRAST,
+            VideoSetting::Hue(_) => VideoSettings::SL_VIDEO_SETTINGS_HUE,
+            VideoSetting::Saturation(_) => VideoSettings::SL_VIDEO_SETTINGS_SATURATION,
+            VideoSetting::Sharpness(_) => VideoSettings::SL_VIDEO_SETTINGS_SHARPNESS,
+            VideoSetting::Gamma(_) => VideoSettings::SL_VIDEO_SETTINGS_GAMMA,
+            VideoSetting::Gain(_) => VideoSettings::SL_VIDEO_SETTINGS_GAIN,
+            VideoSetting::Exposure(_) => VideoSettings::SL_VIDEO_SETTINGS_EXPOSURE,
+            VideoSetting::AutoExposureGain(_) => VideoSettings::SL_VIDEO_SETTINGS_AEC_AGC,
+            VideoSetting::WhiteBalance(_) => {
+                VideoSettings::SL_VIDEO_SETTINGS_WHITEBALANCE_TEMPERATURE
+            }
+            VideoSetting::AutoWhiteBalance(_) => VideoSettings::SL_VIDEO_SETTINGS_WHITEBALANCE_AUTO,
+            VideoSetting::Led(_) => VideoSettings::SL_VIDEO_SETTINGS_LED_STATUS,
+        }
+    }
+
+    pub fn raw_value(&self) -> c_int {
+        match *self {
+            VideoSetting::Brightness(value)
+            | VideoSetting::Contrast(value)
+            | VideoSetting::Hue(value)
+            | VideoSetting::Saturation(value)
+            | VideoSetting::Sharpness(value)
+            | VideoSetting::Gamma(value) => value as c_int,
+            VideoSetting::Gain(Percent(value)) | VideoSetting::Exposure(Percent(value)) => {
+                value as c_int
+            }
+            VideoSetting::WhiteBalance(Kelvin(value)) => value as c_int,
+            VideoSetting::AutoExposureGain(yes)
+            | VideoSetting::AutoWhiteBalance(yes)
+            | VideoSetting::Led(yes) => yes as c_int,
+        }
+    }
+
+    /// Builds the setting from the raw value returned by the SDK.
+    pub fn from_raw(kind: VideoSettings, value: c_int) -> Result<Self> {
+        use VideoSettings as V;
+
+        let to_u8 = || value_to_u8(kind, value);
+
+        let setting = match kind {
+            V::SL_VIDEO_SETTINGS_BRIGHTNESS => VideoSetting::Brightness(to_u8()?),
+            V::SL_VIDEO_SETTINGS_CONTRAST => VideoSetting::Contrast(to_u8()?),
+            V::SL_VIDEO_SETTINGS_HUE => VideoSetting::Hue(to_u8()?),
+            V::SL_VIDEO_SETTINGS_SATURATION => VideoSetting::Saturation(to_u8()?),
+            V::SL_VIDEO_SETTINGS_SHARPNESS => VideoSetting::Sharpness(to_u8()?),
+            V::SL_VIDEO_SETTINGS_GAMMA => VideoSetting::Gamma(to_u8()?),
+            V::SL_VIDEO_SETTINGS_GAIN => VideoSetting::Gain(Percent(to_u8()?)),
+            V::SL_VIDEO_SETTINGS_EXPOSURE => VideoSetting::Exposure(Percent(to_u8()?)),
+            V::SL_VIDEO_SETTINGS_AEC_AGC => VideoSetting::AutoExposureGain(to_u8()? != 0),
+            V::SL_VIDEO_SETTINGS_WHITEBALANCE_TEMPERATURE => {
+                VideoSetting::WhiteBalance(Kelvin(value_to_u16(kind, value)?))
+            }
+            V::SL_VIDEO_SETTINGS_WHITEBALANCE_AUTO => VideoSetting::AutoWhiteBalance(to_u8()? != 0),
+            V::SL_VIDEO_SETTINGS_LED_STATUS => VideoSetting::Led(to_u8()? != 0),
+            kind => {
+                return Err(Error::Desc(
+                    format!("video setting {:?} does not carry a scalar value", kind).into(),
+                ))
+            }
+        };
+
+        Ok(setting)
+    }
+
+    /// Checks if the value is within the range documented by the SDK.
+    pub fn validate(&self) -> Result<()> {
+        let (min, max) = match *self {
+            VideoSetting::Brightness(_)
+            | VideoSetting::Contrast(_)
+            | VideoSetting::Saturation(_)
+            | VideoSetting::Sharpness(_) => (0, 8),
+            VideoSetting::Hue(_) => (0, 11),
+            VideoSetting::Gamma(_) => (1, 9),
+            VideoSetting::Gain(_) | VideoSetting::Exposure(_) => (0, 100),
+            VideoSetting::WhiteBalance(Kelvin(kelvin)) => {
+                ensure!(
+                    kelvin % 100 == 0,
+                    "white balance must be a multiple of 100K, but get {}K",
+                    kelvin
+                );
+                (2800, 6500)
+            }
+            VideoSetting::AutoExposureGain(_)
+            | VideoSetting::AutoWhiteBalance(_)
+            | VideoSetting::Led(_) => return Ok(()),
+        };
+
+        let value = self.raw_value();
+        ensure!(
+            (min..=max).contains(&value),
+            "{:?} is out of range, expect a value within {} and {}",
+            self,
+            min,
+            max
+        );
+        Ok(())
+    }
+}
+
+/// The values of all video settings of a camera.
+///
+/// It is read by [Camera::video_settings] and applied back by
+/// [Camera::apply_video_settings].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VideoSettingsSnapshot {
+    pub brightness: u8,
+    pub contrast: u8,
+    pub hue: u8,
+    pub saturation: u8,
+    pub sharpness: u8,
+    pub gamma: u8,
+    pub gain: Percent,
+    pub exposure: Percent,
+    pub auto_exposure_gain: bool,
+    pub white_balance: Kelvin,
+    pub auto_white_balance: bool,
+    pub led: bool,
+}
+
+impl VideoSettingsSnapshot {
+    /// Lists the settings in the order they are applied.
+    ///
+    /// The automatic modes come after the manual values, because setting a
+    /// manual value turns the corresponding automatic mode off. The manual
+    /// values are left out while their automatic mode is on, since they are
+    /// whatever the camera picked and may not be valid settings, e.g. a
+    /// white balance that is not a multiple of 100K.
+    pub fn settings(&self) -> Vec<VideoSetting> {
+        let mut settings = vec![
+            VideoSetting::Brightness(self.brightness),
+            VideoSetting::Contrast(self.contrast),
+            VideoSetting::Hue(self.hue),
+            VideoSetting::Saturation(self.saturation),
+            VideoSetting::Sharpness(self.sharpness),
+            VideoSetting::Gamma(self.gamma),
+        ];
+        if !self.auto_exposure_gain {
+            settings.push(VideoSetting::Gain(self.gain));
+            settings.push(VideoSetting::Exposure(self.exposure));
+        }
+        settings.push(VideoSetting::AutoExposureGain(self.auto_exposure_gain));
+        if !self.auto_white_balance {
+            settings.push(VideoSetting::WhiteBalance(self.white_balance));
+        }
+        settings.push(VideoSetting::AutoWhiteBalance(self.auto_white_balance));
+        settings.push(VideoSetting::Led(self.led));
+        settings
+    }
+}
+
+impl<I, R, S> Camera<I, R, S> {
+    /// Gets the current value of a video setting.
+    pub fn get(&mut self, kind: VideoSettings) -> Result<VideoSetting> {
+        let value = self.settings(kind)?;
+        VideoSetting::from_raw(kind, value)
+    }
+
+    /// Validates and applies a video setting.
+    pub fn set(&mut self, setting: VideoSetting) -> Result<()> {
+        setting.validate()?;
+        self.set_settings(setting.kind(), setting.raw_value());
+        Ok(())
+    }
+
+    /// Restores the default value of a video setting.
+    pub fn reset(&mut self, kind: VideoSettings) {
+        self.set_settings(kind, -1);
+    }
+
+    pub fn set_auto_exposure_gain(&mut self) {
+        self.set_settings(VideoSettings::SL_VIDEO_SETTINGS_AEC_AGC, 1);
+    }
+
+    pub fn set_white_balance(&mut self, kelvin: Kelvin) -> Result<()> {
+        self.set(VideoSetting::WhiteBalance(kelvin))
+    }
+
+    pub fn set_auto_white_balance(&mut self) {
+        self.set_settings(VideoSettings::SL_VIDEO_SETTINGS_WHITEBALANCE_AUTO, 1);
+    }
+
+    /// Sets the region of interest for automatic exposure and gain.
+    ///
+    /// The rectangle must be non-empty and lie within the camera resolution.
+    pub fn set_exposure_roi(&mut self, side: Side, rect: Rect) -> Result<()> {
+        let resolution = self.resolution();
+        ensure!(
+            !rect.is_empty(),
+            "the region of interest {:?} is empty",
+            rect
+        );
+        ensure!(
+            rect.is_within(resolution),
+            "the region of interest {:?} exceeds the image size {}x{}",
+            rect,
+            resolution.0,
+            resolution.1
+        );
+
+        let mut roi = rect.to_raw()?;
+        let id = self.id();
+        let code = unsafe { sys::sl_set_roi_for_aec_agc(id, side, &mut roi, false) };
+        code_to_result(code)
+            .map_err(|err| err.with_context(self.inner.context(Operation::SetExposureRoi)))
+    }
+
+    /// Gets the region of interest for automatic exposure and gain.
+    pub fn exposure_roi(&mut self, side: Side) -> Result<Rect> {
+        let mut roi = sys::SL_Rect {
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+        };
+        let id = self.id();
+        let code = unsafe { sys::sl_get_roi_for_aec_agc(id, side, &mut roi) };
+        code_to_result(code)
+            .map_err(|err| err.with_context(self.inner.context(Operation::GetExposureRoi)))?;
+        Rect::from_raw(&roi)
+    }
+
+    /// Resets the region of interest for automatic exposure and gain to the
+    /// full image.
+    pub fn reset_exposure_roi(&mut self, side: Side) -> Result<()> {
+        let (width, height) = self.resolution();
+        let mut roi = Rect::new(0, 0, width, height).to_raw()?;
+        let id = self.id();
+        let code = unsafe { sys::sl_set_roi_for_aec_agc(id, side, &mut roi, true) };
+        code_to_result(code)
+            .map_err(|err| err.with_context(self.inner.context(Operation::SetExposureRoi)))
+    }
+
+    /// Reads all video settings at once.
+    pub fn video_settings(&mut self) -> Result<VideoSettingsSnapshot> {
+        use VideoSettings as V;
+
+        let mut get_u8 = |kind| -> Result<u8> { value_to_u8(kind, self.settings(kind)?) };
+
+        let brightness = get_u8(V::SL_VIDEO_SETTINGS_BRIGHTNESS)?;
+        let contrast = get_u8(V::SL_VIDEO_SETTINGS_CONTRAST)?;
+        let hue = get_u8(V::SL_VIDEO_SETTINGS_HUE)?;
+        let saturation = get_u8(V::SL_VIDEO_SETTINGS_SATURATION)?;
+        let sharpness = get_u8(V::SL_VIDEO_SETTINGS_SHARPNESS)?;
+        let gamma = get_u8(V::SL_VIDEO_SETTINGS_GAMMA)?;
+        let gain = Percent(get_u8(V::SL_VIDEO_SETTINGS_GAIN)?);
+        let exposure = Percent(get_u8(V::SL_VIDEO_SETTINGS_EXPOSURE)?);
+        let auto_exposure_gain = get_u8(V::SL_VIDEO_SETTINGS_AEC_AGC)? != 0;
+        let auto_white_balance = get_u8(V::SL_VIDEO_SETTINGS_WHITEBALANCE_AUTO)? != 0;
+        let led = get_u8(V::SL_VIDEO_SETTINGS_LED_STATUS)? != 0;
+        let white_balance = {
+            let kind = V::SL_VIDEO_SETTINGS_WHITEBALANCE_TEMPERATURE;
+            Kelvin(value_to_u16(kind, self.settings(kind)?)?)
+        };
+
+        Ok(VideoSettingsSnapshot {
+            brightness,
+            contrast,
+            hue,
+            saturation,
+            sharpness,
+            gamma,
+            gain,
+            exposure,
+            auto_exposure_gain,
+            white_balance,
+            auto_white_balance,
+            led,
+        })
+    }
+
+    /// Applies all video settings in the snapshot.
+    ///
+    /// All values are validated before any of them is applied. Manual
+    /// values are skipped while their automatic mode is on.
+    pub fn apply_video_settings(&mut self, snapshot: &VideoSettingsSnapshot) -> Result<()> {
+        let settings = snapshot.settings();
+        settings.iter().try_for_each(|setting| setting.validate())?;
+
+        for setting in settings {
+            self.set_settings(setting.kind(), setting.raw_value());
+        }
+
+        Ok(())
+    }
+}
+
+fn value_to_u8(kind: VideoSettings, value: c_int) -> Result<u8> {
+    u8::try_from(value).map_err(|_| invalid_value(kind, value))
+}
+
+fn value_to_u16(kind: VideoSettings, value: c_int) -> Result<u16> {
+    u16::try_from(value).map_err(|_| invalid_value(kind, value))
+}
+
+fn invalid_value(kind: VideoSettings, value: c_int) -> Error {
+    Error::Desc(format!("invalid value {} for video setting {:?}", value, kind).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> VideoSettingsSnapshot {
+        VideoSettingsSnapshot {
+            brightness: 4,
+            contrast: 4,
+            hue: 0,
+            saturation: 4,
+            sharpness: 4,
+            gamma: 8,
+            gain: Percent(37),
+            exposure: Percent(42),
+            auto_exposure_gain: false,
+            white_balance: Kelvin(4600),
+            auto_white_balance: false,
+            led: true,
+        }
+    }
+
+    #[test]
+    fn validate_ranges() {
+        VideoSetting::Brightness(8).validate().unwrap();
+        VideoSetting::Brightness(9).validate().unwrap_err();
+        VideoSetting::Hue(11).validate().unwrap();
+        VideoSetting::Hue(12).validate().unwrap_err();
+        VideoSetting::Gamma(0).validate().unwrap_err();
+        VideoSetting::Gamma(1).validate().unwrap();
+        VideoSetting::Gamma(10).validate().unwrap_err();
+        VideoSetting::Led(true).validate().unwrap();
+    }
+
+    #[test]
+    fn validate_percent() {
+        VideoSetting::Gain(Percent(0)).validate().unwrap();
+        VideoSetting::Exposure(Percent(100)).validate().unwrap();
+        VideoSetting::Gain(Percent(101)).validate().unwrap_err();
+        VideoSetting::Exposure(Percent(255)).validate().unwrap_err();
+    }
+
+    #[test]
+    fn validate_kelvin() {
+        VideoSetting::WhiteBalance(Kelvin(2800)).validate().unwrap();
+        VideoSetting::WhiteBalance(Kelvin(6500)).validate().unwrap();
+        VideoSetting::WhiteBalance(Kelvin(2700))
+            .validate()
+            .unwrap_err();
+        VideoSetting::WhiteBalance(Kelvin(6600))
+            .validate()
+            .unwrap_err();
+
+        let err = VideoSetting::WhiteBalance(Kelvin(4650))
+            .validate()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "white balance must be a multiple of 100K, but get 4650K"
+        );
+    }
+
+    #[test]
+    fn raw_value_round_trip() {
+        for setting in snapshot().settings() {
+            let back = VideoSetting::from_raw(setting.kind(), setting.raw_value()).unwrap();
+            assert_eq!(back, setting);
+        }
+    }
+
+    #[test]
+    fn skip_manual_values_in_auto_mode() {
+        let manual = snapshot().settings();
+        assert_eq!(manual.len(), 12);
+        assert!(manual.iter().all(|setting| setting.validate().is_ok()));
+
+        // Values picked by the camera in automatic mode need not be valid.
+        let auto = VideoSettingsSnapshot {
+            auto_exposure_gain: true,
+            auto_white_balance: true,
+            white_balance: Kelvin(4637),
+            ..snapshot()
+        }
+        .settings();
+        assert_eq!(auto.len(), 9);
+        assert!(auto.iter().all(|setting| setting.validate().is_ok()));
+        assert!(!auto.iter().any(|setting| matches!(
+            setting,
+            VideoSetting::Gain(_) | VideoSetting::Exposure(_) | VideoSetting::WhiteBalance(_)
+        )));
+    }
+}
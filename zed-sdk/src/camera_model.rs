@@ -0,0 +1,253 @@
+use crate::{CameraParameters, Vector3};
+
+const UNDISTORT_ITERATIONS: usize = 20;
+
+/// The pinhole model of a camera with Brown-Conrady distortion.
+///
+/// Points are in the image coordinate system, i.e. x right, y down and
+/// z forward, in any unit. Pixels are `(x, y)` in the image at
+/// [resolution](Self::resolution).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PinholeModel {
+    pub fx: f32,
+    pub fy: f32,
+    pub cx: f32,
+    pub cy: f32,
+    /// The distortion factors `[k1, k2, p1, p2, k3]`.
+    pub distortion: [f64; 5],
+    width: usize,
+    height: usize,
+}
+
+impl PinholeModel {
+    pub fn new(params: &CameraParameters) -> Self {
+        Self {
+            fx: params.fx,
+            fy: params.fy,
+            cx: params.cx,
+            cy: params.cy,
+            distortion: params.disto,
+            width: params.image_size.width as usize,
+            height: params.image_size.height as usize,
+        }
+    }
+
+    pub fn resolution(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Gets the horizontal and vertical field of view in degrees.
+    pub fn fov(&self) -> (f32, f32) {
+        let fov =
+            |size: usize, focal: f32| (2.0 * (size as f32 / (2.0 * focal)).atan()).to_degrees();
+        (fov(self.width, self.fx), fov(self.height, self.fy))
+    }
+
+    /// Scales the intrinsics for images retrieved at another resolution.
+    ///
+    /// The distortion factors apply to normalized coordinates, so they are
+    /// kept as is. Returns `None` if either resolution has a zero size,
+    /// e.g. for calibration data that was never filled.
+    pub fn rescale(&self, width: usize, height: usize) -> Option<Self> {
+        if [width, height, self.width, self.height].contains(&0) {
+            return None;
+        }
+
+        let sx = width as f32 / self.width as f32;
+        let sy = height as f32 / self.height as f32;
+
+        Some(Self {
+            fx: self.fx * sx,
+            fy: self.fy * sy,
+            cx: self.cx * sx,
+            cy: self.cy * sy,
+            width,
+            height,
+            ..*self
+        })
+    }
+
+    /// Projects the point to a pixel, or returns `None` if the point is not
+    /// in front of the camera.
+    ///
+    /// The pixel may lie outside the image.
+    pub fn project(&self, point: Vector3) -> Option<(f32, f32)> {
+        let Vector3 { x, y, z } = point;
+        if !(z > 0.0 && x.is_finite() && y.is_finite() && z.is_finite()) {
+            return None;
+        }
+
+        let (xd, yd) = self.distort((x / z, y / z));
+        Some((self.fx * xd + self.cx, self.fy * yd + self.cy))
+    }
+
+    /// Lifts the pixel to the point at the depth, i.e. the z coordinate.
+    pub fn unproject(&self, pixel: (f32, f32), depth: f32) -> Vector3 {
+        let (u, v) = pixel;
+        let (x, y) = self.undistort(((u - self.cx) / self.fx, (v - self.cy) / self.fy));
+        Vector3 {
+            x: x * depth,
+            y: y * depth,
+            z: depth,
+        }
+    }
+
+    /// Applies the distortion to normalized image coordinates.
+    pub fn distort(&self, point: (f32, f32)) -> (f32, f32) {
+        let (x, y) = (point.0 as f64, point.1 as f64);
+        let [k1, k2, p1, p2, k3] = self.distortion;
+
+        let r2 = x * x + y * y;
+        let radial = 1.0 + r2 * (k1 + r2 * (k2 + r2 * k3));
+        let xd = x * radial + 2.0 * p1 * x * y + p2 * (r2 + 2.0 * x * x);
+        let yd = y * radial + p1 * (r2 + 2.0 * y * y) + 2.0 * p2 * x * y;
+        (xd as f32, yd as f32)
+    }
+
+    /// Removes the distortion from normalized image coordinates.
+    ///
+    /// The distortion has no closed-form inverse, so it is solved by fixed
+    /// point iteration, which converges for the moderate distortion of
+    /// ZED lenses.
+    pub fn undistort(&self, point: (f32, f32)) -> (f32, f32) {
+        let (xd, yd) = (point.0 as f64, point.1 as f64);
+        let [k1, k2, p1, p2, k3] = self.distortion;
+        let (mut x, mut y) = (xd, yd);
+
+        for _ in 0..UNDISTORT_ITERATIONS {
+            let r2 = x * x + y * y;
+            let radial = 1.0 + r2 * (k1 + r2 * (k2 + r2 * k3));
+            let dx = 2.0 * p1 * x * y + p2 * (r2 + 2.0 * x * x);
+            let dy = p1 * (r2 + 2.0 * y * y) + 2.0 * p2 * x * y;
+            x = (xd - dx) / radial;
+            y = (yd - dy) / radial;
+        }
+
+        (x as f32, y as f32)
+    }
+}
+
+impl From<&CameraParameters> for PinholeModel {
+    fn from(params: &CameraParameters) -> Self {
+        Self::new(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::*;
+
+    fn params(disto: [f64; 5]) -> CameraParameters {
+        CameraParameters {
+            fx: 700.0,
+            fy: 700.0,
+            cx: 640.0,
+            cy: 360.0,
+            disto,
+            v_fov: 0.0,
+            h_fov: 0.0,
+            d_fov: 0.0,
+            image_size: sys::SL_Resolution {
+                width: 1280,
+                height: 720,
+            },
+        }
+    }
+
+    const DISTO: [f64; 5] = [-0.17, 0.027, 0.0005, -0.0003, 0.0];
+
+    fn assert_close(a: f32, b: f32, eps: f32) {
+        assert!((a - b).abs() <= eps, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn project_without_distortion() {
+        let model = PinholeModel::new(&params([0.0; 5]));
+        let (u, v) = model
+            .project(Vector3 {
+                x: 1.0,
+                y: -0.5,
+                z: 2.0,
+            })
+            .unwrap();
+        assert_close(u, 640.0 + 350.0, 1e-3);
+        assert_close(v, 360.0 - 175.0, 1e-3);
+    }
+
+    #[test]
+    fn reject_points_behind_camera() {
+        let model = PinholeModel::new(&params(DISTO));
+        let point = |z| Vector3 { x: 0.1, y: 0.1, z };
+        assert!(model.project(point(0.0)).is_none());
+        assert!(model.project(point(-1.0)).is_none());
+        assert!(model.project(point(f32::NAN)).is_none());
+    }
+
+    #[test]
+    fn distortion_round_trip() {
+        let model = PinholeModel::new(&params(DISTO));
+        for &(x, y) in &[(0.0, 0.0), (0.3, -0.2), (-0.5, 0.4), (0.6, 0.35)] {
+            let (xd, yd) = model.distort((x, y));
+            let (xu, yu) = model.undistort((xd, yd));
+            assert_close(xu, x, 1e-5);
+            assert_close(yu, y, 1e-5);
+        }
+    }
+
+    #[test]
+    fn unproject_inverts_project() {
+        let model = PinholeModel::new(&params(DISTO));
+        let point = Vector3 {
+            x: -0.8,
+            y: 0.3,
+            z: 2.5,
+        };
+        let pixel = model.project(point).unwrap();
+        let back = model.unproject(pixel, point.z);
+        assert_close(back.x, point.x, 1e-4);
+        assert_close(back.y, point.y, 1e-4);
+        assert_close(back.z, point.z, 0.0);
+    }
+
+    #[test]
+    fn rescale_to_half_resolution() {
+        let model = PinholeModel::new(&params(DISTO));
+        let half = model.rescale(640, 360).unwrap();
+        assert_eq!(half.resolution(), (640, 360));
+        assert_close(half.fx, 350.0, 0.0);
+        assert_close(half.cy, 180.0, 0.0);
+        assert_eq!(half.distortion, DISTO);
+
+        let point = Vector3 {
+            x: 0.4,
+            y: 0.2,
+            z: 3.0,
+        };
+        let (u, v) = model.project(point).unwrap();
+        let (hu, hv) = half.project(point).unwrap();
+        assert_close(hu, u / 2.0, 1e-3);
+        assert_close(hv, v / 2.0, 1e-3);
+        assert_eq!(half.fov(), model.fov());
+    }
+
+    #[test]
+    fn reject_rescale_with_zero_size() {
+        let model = PinholeModel::new(&params(DISTO));
+        assert!(model.rescale(0, 360).is_none());
+        assert!(model.rescale(640, 0).is_none());
+
+        let mut empty = params(DISTO);
+        empty.image_size.width = 0;
+        empty.image_size.height = 0;
+        assert!(PinholeModel::new(&empty).rescale(640, 360).is_none());
+    }
+
+    #[test]
+    fn field_of_view() {
+        let model = PinholeModel::new(&params([0.0; 5]));
+        let (h, v) = model.fov();
+        assert_close(h, 84.9, 0.1);
+        assert_close(v, 54.4, 0.1);
+    }
+}
@@ -3,12 +3,14 @@ pub use num_traits::{NumCast, ToPrimitive};
 pub use std::{
     borrow::Cow,
     ffi::{CString, OsStr},
+    fmt::{self, Display},
     marker::PhantomData,
     mem,
-    net::{SocketAddr, SocketAddrV4, SocketAddrV6},
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
     os,
     os::raw::c_int,
     path::{Path, PathBuf},
     ptr,
+    time::Duration,
 };
 pub use zed_sdk_sys as sys;
@@ -0,0 +1,195 @@
+use crate::{
+    common::*, CameraBuilder, DynCamera, InitParameters, Input, RecordingParams, Result,
+    RuntimeParams, StreamingParameters, VideoSetting,
+};
+use serde::{Deserialize, Serialize};
+
+/// The description of a camera loadable from configuration files.
+///
+/// The fields other than `input` can be omitted and take their default
+/// values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraConfig<'a> {
+    pub input: Input<'a>,
+    #[serde(default)]
+    pub output_file: Option<Cow<'a, Path>>,
+    #[serde(default)]
+    pub opt_settings_path: Option<Cow<'a, Path>>,
+    #[serde(default)]
+    pub opencv_calib_path: Option<Cow<'a, Path>>,
+    #[serde(default)]
+    pub init_parameters: InitParameters,
+    #[serde(default)]
+    pub runtime_parameters: RuntimeParams,
+    #[serde(default)]
+    pub recording: Option<RecordingConfig<'a>>,
+    #[serde(default)]
+    pub streaming: Option<StreamingParameters>,
+    /// Video settings applied in order after the camera is opened.
+    #[serde(default)]
+    pub video_settings: Vec<VideoSetting>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RecordingConfig<'a> {
+    pub output_file: Cow<'a, Path>,
+    #[serde(flatten)]
+    pub params: RecordingParams,
+}
+
+impl<'a> CameraBuilder<'a> {
+    /// Opens the camera described by the configuration.
+    ///
+    /// The video settings are applied, and then recording and streaming are
+    /// enabled if they are configured. The runtime parameters are not used
+    /// here and are meant to be passed to [grab](crate::Camera::grab).
+    pub fn from_config(config: &CameraConfig<'a>) -> Result<DynCamera> {
+        let mut builder = CameraBuilder::new();
+        builder.output_file = config.output_file.clone();
+        builder.opt_settings_path = config.opt_settings_path.clone();
+        builder.opencv_calib_path = config.opencv_calib_path.clone();
+        builder.params = config.init_parameters;
+
+        let mut camera = match &config.input {
+            Input::Svo(path) => builder.open_svo(path)?.into_dyn(),
+            Input::Stream(addr) => builder.open_stream(*addr)?.into_dyn(),
+            Input::Usb(id) => builder.open_usb(*id)?.into_dyn(),
+            Input::Serial(sn) => builder.open_serial(*sn)?.into_dyn(),
+        };
+
+        for &setting in &config.video_settings {
+            camera.set(setting)?;
+        }
+
+        if let Some(recording) = &config.recording {
+            camera.enable_recording(&recording.output_file, recording.params.clone())?;
+        }
+
+        if let Some(params) = &config.streaming {
+            camera.enable_streaming(params.clone())?;
+        }
+
+        Ok(camera)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Percent, ReferenceFrame, StreamingCodec, VideoSettings};
+
+    #[test]
+    fn fill_defaults_from_minimal_toml() {
+        let config: CameraConfig = toml::from_str(
+            r#"
+            input = { serial = 12345 }
+
+            [init_parameters]
+            resolution = "HD1080"
+            depth_mode = "PERFORMANCE"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.input, Input::Serial(12345));
+        assert!(config.output_file.is_none());
+        assert!(config.recording.is_none());
+        assert!(config.streaming.is_none());
+        assert!(config.video_settings.is_empty());
+        assert_eq!(config.runtime_parameters, RuntimeParams::default());
+
+        // The omitted fields keep the defaults of the SDK rather than zeros.
+        let init = config.init_parameters;
+        let default = InitParameters::default();
+        assert_eq!(init.resolution, sys::SL_RESOLUTION::SL_RESOLUTION_HD1080);
+        assert_eq!(
+            init.depth_mode,
+            sys::SL_DEPTH_MODE::SL_DEPTH_MODE_PERFORMANCE
+        );
+        assert_eq!(init.coordinate_unit, default.coordinate_unit);
+        assert_eq!(init.sdk_gpu_id, -1);
+        assert_eq!(init.depth_minimum_distance, -1.0);
+        assert_eq!(init.open_timeout_sec, 5.0);
+        assert!(init.depth_stabilization);
+        assert!(init.enable_image_enhancement);
+    }
+
+    #[test]
+    fn round_trip_toml_and_json() {
+        let init_parameters = InitParameters {
+            coordinate_unit: sys::SL_UNIT::SL_UNIT_METER,
+            camera_fps: 30,
+            ..Default::default()
+        };
+
+        let config = CameraConfig {
+            input: Input::svo_file(Path::new("/data/run.svo")),
+            output_file: None,
+            opt_settings_path: Some(Cow::Borrowed(Path::new("/etc/zed"))),
+            opencv_calib_path: None,
+            init_parameters,
+            runtime_parameters: RuntimeParams::new()
+                .reference_frame(ReferenceFrame::World)
+                .confidence_threshold(50),
+            recording: Some(RecordingConfig {
+                output_file: Cow::Borrowed(Path::new("/data/out.svo")),
+                params: RecordingParams {
+                    compression_mode: sys::SL_SVO_COMPRESSION_MODE::SL_SVO_COMPRESSION_MODE_H265,
+                    ..Default::default()
+                },
+            }),
+            streaming: Some(StreamingParameters {
+                codec: StreamingCodec::SL_STREAMING_CODEC_H264,
+                port: 30002,
+                ..Default::default()
+            }),
+            video_settings: vec![
+                VideoSetting::Exposure(Percent(40)),
+                VideoSetting::Led(false),
+            ],
+        };
+
+        let text = toml::to_string(&config).unwrap();
+        assert!(text.contains(r#"coordinate_unit = "METER""#));
+        assert!(text.contains(r#"reference_frame = "WORLD""#));
+        assert!(text.contains(r#"compression_mode = "H265""#));
+        let from_toml: CameraConfig = toml::from_str(&text).unwrap();
+        assert_same(&config, &from_toml);
+
+        let text = serde_json::to_string(&config).unwrap();
+        assert!(text.contains(r#""codec":"H264""#));
+        let from_json: CameraConfig = serde_json::from_str(&text).unwrap();
+        assert_same(&config, &from_json);
+    }
+
+    #[test]
+    fn reject_unknown_enum_names() {
+        let err = serde_json::from_str::<InitParameters>(r#"{ "resolution": "HD4K" }"#)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("HD4K"), "{}", err);
+
+        assert_eq!(
+            serde_json::from_str::<VideoSettings>(r#""GAIN""#).unwrap(),
+            VideoSettings::SL_VIDEO_SETTINGS_GAIN
+        );
+        assert!(serde_json::from_str::<VideoSettings>(r#""LAST""#).is_err());
+        assert!(serde_json::to_string(&VideoSettings::SL_VIDEO_SETTINGS_LAST).is_err());
+    }
+
+    fn assert_same(lhs: &CameraConfig, rhs: &CameraConfig) {
+        // InitParameters is a bindgen struct without PartialEq.
+        assert_eq!(
+            format!("{:?}", lhs.init_parameters),
+            format!("{:?}", rhs.init_parameters)
+        );
+        assert_eq!(lhs.input, rhs.input);
+        assert_eq!(lhs.output_file, rhs.output_file);
+        assert_eq!(lhs.opt_settings_path, rhs.opt_settings_path);
+        assert_eq!(lhs.opencv_calib_path, rhs.opencv_calib_path);
+        assert_eq!(lhs.runtime_parameters, rhs.runtime_parameters);
+        assert_eq!(lhs.recording, rhs.recording);
+        assert_eq!(lhs.streaming, rhs.streaming);
+        assert_eq!(lhs.video_settings, rhs.video_settings);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{common::*, CameraState, Model, UsbDevice, MAX_CAMERA_PLUGIN};
+
+/// A connected camera listed by [get_device_list].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DeviceInfo {
+    /// The device index passed to
+    /// [CameraBuilder::open_usb](crate::CameraBuilder::open_usb).
+    pub id: c_int,
+    /// The serial number. It is not provided on Windows.
+    pub serial_number: c_int,
+    /// The camera model, or `None` if the model is unknown to this crate.
+    pub model: Option<Model>,
+    /// The camera state. Unknown states are reported as not available.
+    pub state: CameraState,
+}
+
+impl DeviceInfo {
+    pub fn is_available(&self) -> bool {
+        self.state == CameraState::SL_CAMERA_STATE_AVAILABLE
+    }
+}
+
+/// The memory layout of `SL_DeviceProperties` with the enums read as plain
+/// integers, so that values unknown to the bindings are not UB.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct RawDeviceProperties {
+    camera_state: c_int,
+    id: c_int,
+    camera_model: c_int,
+    sn: c_int,
+}
+
+const _: () =
+    assert!(mem::size_of::<RawDeviceProperties>() == mem::size_of::<sys::SL_DeviceProperties>());
+const _: () =
+    assert!(mem::align_of::<RawDeviceProperties>() == mem::align_of::<sys::SL_DeviceProperties>());
+
+impl From<RawDeviceProperties> for DeviceInfo {
+    fn from(raw: RawDeviceProperties) -> Self {
+        Self {
+            id: raw.id,
+            serial_number: raw.sn,
+            model: Model::try_from(raw.camera_model).ok(),
+            state: CameraState::try_from(raw.camera_state)
+                .unwrap_or(CameraState::SL_CAMERA_STATE_NOT_AVAILABLE),
+        }
+    }
+}
+
+/// Lists the connected cameras.
+pub fn get_device_list() -> Vec<DeviceInfo> {
+    // The SDK does not take the capacity of the list, so leave room for as
+    // many cameras as can be plugged in between the two calls.
+    let capacity = number_of_connected_cameras() + MAX_CAMERA_PLUGIN;
+    let mut device_list = vec![RawDeviceProperties::default(); capacity];
+    let mut nb_devices: c_int = 0;
+
+    unsafe {
+        sys::sl_get_device_list(
+            device_list.as_mut_ptr() as *mut sys::SL_DeviceProperties,
+            &mut nb_devices as *mut c_int,
+        );
+    }
+
+    let len = (nb_devices.max(0) as usize).min(capacity);
+    device_list.truncate(len);
+    device_list.into_iter().map(DeviceInfo::from).collect()
+}
+
+/// Gets the number of connected ZED cameras.
+pub fn number_of_connected_cameras() -> usize {
+    let count = unsafe { sys::sl_get_number_zed_connected() };
+    count.max(0) as usize
+}
+
+/// Checks whether a USB device of the vendor is connected.
+pub fn find_usb_device(device: UsbDevice) -> bool {
+    unsafe { sys::sl_find_usb_device(device) }
+}
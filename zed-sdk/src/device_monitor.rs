@@ -0,0 +1,250 @@
+use crate::{common::*, get_device_list, DeviceInfo};
+use std::{
+    collections::BTreeMap,
+    sync::mpsc::{self, RecvTimeoutError},
+    thread::{self, JoinHandle},
+};
+
+/// A change in the connected cameras reported by [DeviceMonitor].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeviceEvent {
+    Added(DeviceInfo),
+    Removed(DeviceInfo),
+    /// The state or device index of a connected camera changed.
+    StateChanged {
+        old: DeviceInfo,
+        new: DeviceInfo,
+    },
+}
+
+/// Polls the connected cameras on a background thread and reports the
+/// changes.
+///
+/// Cameras are matched by serial number, which is not provided on Windows.
+/// Cameras whose serial number reads as 0 are matched by serial number and
+/// device index instead, so a new index is reported as a removal and an
+/// addition. The cameras connected when the monitor starts are reported as
+/// [DeviceEvent::Added]. The thread is stopped when the monitor is dropped.
+#[derive(Debug)]
+pub struct DeviceMonitor {
+    events: mpsc::Receiver<DeviceEvent>,
+    stop: Option<mpsc::Sender<()>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl DeviceMonitor {
+    /// Starts polling the device list at the interval.
+    pub fn start(interval: Duration) -> Self {
+        let (event_tx, event_rx) = mpsc::channel();
+        let (stop_tx, stop_rx) = mpsc::channel();
+
+        let handle = thread::spawn(move || {
+            let mut known = BTreeMap::new();
+
+            loop {
+                for event in diff_devices(&mut known, get_device_list()) {
+                    if event_tx.send(event).is_err() {
+                        return;
+                    }
+                }
+
+                match stop_rx.recv_timeout(interval) {
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Ok(()) | Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+        });
+
+        Self {
+            events: event_rx,
+            stop: Some(stop_tx),
+            handle: Some(handle),
+        }
+    }
+
+    /// Blocks until the next event.
+    pub fn recv(&self) -> Option<DeviceEvent> {
+        self.events.recv().ok()
+    }
+
+    /// Blocks until the next event or the timeout elapses.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<DeviceEvent> {
+        self.events.recv_timeout(timeout).ok()
+    }
+
+    /// Gets the next event if any, without blocking.
+    pub fn try_recv(&self) -> Option<DeviceEvent> {
+        self.events.try_recv().ok()
+    }
+
+    /// Iterates over the events, blocking until each one arrives.
+    pub fn iter(&self) -> mpsc::Iter<'_, DeviceEvent> {
+        self.events.iter()
+    }
+
+    /// Iterates over the pending events without blocking.
+    pub fn try_iter(&self) -> mpsc::TryIter<'_, DeviceEvent> {
+        self.events.try_iter()
+    }
+
+    /// Stops the polling thread and waits for it to exit.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    /// Signals the polling thread to exit and joins it. It does nothing if
+    /// the thread is already stopped.
+    fn shutdown(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for DeviceMonitor {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// The key matching a camera across polls.
+type DeviceKey = (c_int, Option<c_int>);
+
+fn device_key(dev: &DeviceInfo) -> DeviceKey {
+    match dev.serial_number {
+        0 => (0, Some(dev.id)),
+        sn => (sn, None),
+    }
+}
+
+/// Updates the known devices and returns the changes.
+///
+/// The removed cameras come first, ordered by serial number, then the added
+/// and changed cameras in the order of the device list.
+fn diff_devices(
+    known: &mut BTreeMap<DeviceKey, DeviceInfo>,
+    devices: Vec<DeviceInfo>,
+) -> Vec<DeviceEvent> {
+    let current: BTreeMap<_, _> = devices.iter().map(|dev| (device_key(dev), *dev)).collect();
+
+    let mut events: Vec<_> = known
+        .iter()
+        .filter(|(key, _)| !current.contains_key(key))
+        .map(|(_, &old)| DeviceEvent::Removed(old))
+        .collect();
+
+    for new in &devices {
+        match known.get(&device_key(new)) {
+            None => events.push(DeviceEvent::Added(*new)),
+            Some(&old) if old != *new => events.push(DeviceEvent::StateChanged { old, new: *new }),
+            Some(_) => {}
+        }
+    }
+
+    *known = current;
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CameraState, Model};
+
+    fn device(id: c_int, serial_number: c_int, state: CameraState) -> DeviceInfo {
+        DeviceInfo {
+            id,
+            serial_number,
+            model: Some(Model::SL_MODEL_ZED2),
+            state,
+        }
+    }
+
+    const AVAILABLE: CameraState = CameraState::SL_CAMERA_STATE_AVAILABLE;
+    const NOT_AVAILABLE: CameraState = CameraState::SL_CAMERA_STATE_NOT_AVAILABLE;
+
+    #[test]
+    fn report_added_and_removed() {
+        let mut known = BTreeMap::new();
+        let a = device(0, 1001, AVAILABLE);
+        let b = device(1, 1002, AVAILABLE);
+
+        assert_eq!(
+            diff_devices(&mut known, vec![a, b]),
+            vec![DeviceEvent::Added(a), DeviceEvent::Added(b)]
+        );
+        assert_eq!(diff_devices(&mut known, vec![a, b]), vec![]);
+        assert_eq!(
+            diff_devices(&mut known, vec![b]),
+            vec![DeviceEvent::Removed(a)]
+        );
+    }
+
+    #[test]
+    fn order_removed_by_serial_number() {
+        let mut known = BTreeMap::new();
+        let a = device(0, 1003, AVAILABLE);
+        let b = device(1, 1001, AVAILABLE);
+        let c = device(2, 1002, AVAILABLE);
+        let d = device(3, 0, AVAILABLE);
+        diff_devices(&mut known, vec![a, b, c, d]);
+
+        let e = device(4, 1000, AVAILABLE);
+        assert_eq!(
+            diff_devices(&mut known, vec![c, e]),
+            vec![
+                DeviceEvent::Removed(d),
+                DeviceEvent::Removed(b),
+                DeviceEvent::Removed(a),
+                DeviceEvent::Added(e),
+            ]
+        );
+    }
+
+    #[test]
+    fn report_state_changed() {
+        let mut known = BTreeMap::new();
+        let old = device(0, 1001, AVAILABLE);
+        diff_devices(&mut known, vec![old]);
+
+        let new = device(0, 1001, NOT_AVAILABLE);
+        assert_eq!(
+            diff_devices(&mut known, vec![new]),
+            vec![DeviceEvent::StateChanged { old, new }]
+        );
+
+        // The serial number follows the camera across device indices.
+        let moved = device(2, 1001, NOT_AVAILABLE);
+        assert_eq!(
+            diff_devices(&mut known, vec![moved]),
+            vec![DeviceEvent::StateChanged {
+                old: new,
+                new: moved
+            }]
+        );
+    }
+
+    #[test]
+    fn keep_cameras_without_serial_number_apart() {
+        let mut known = BTreeMap::new();
+        let a = device(0, 0, AVAILABLE);
+        let b = device(1, 0, AVAILABLE);
+
+        assert_eq!(
+            diff_devices(&mut known, vec![a, b]),
+            vec![DeviceEvent::Added(a), DeviceEvent::Added(b)]
+        );
+        assert_eq!(
+            diff_devices(&mut known, vec![b]),
+            vec![DeviceEvent::Removed(a)]
+        );
+
+        let c = device(0, 0, NOT_AVAILABLE);
+        assert_eq!(
+            diff_devices(&mut known, vec![c, b]),
+            vec![DeviceEvent::Added(c)]
+        );
+    }
+}
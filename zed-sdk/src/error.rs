@@ -1,21 +1,284 @@
-use crate::{common::*, ErrorCode};
+use crate::{common::*, ErrorCode, InputType, RecordingState, StreamingState};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, thiserror::Error)]
 pub enum Error {
     #[error("ZED error: {0}")]
     Code(ErrorCode),
+    #[error("unknown ZED error code {0}")]
+    Unknown(i32),
     #[error("{0}")]
     Desc(Cow<'static, str>),
+    #[error("{0}")]
+    State(#[from] StateError),
+    /// Shows only the context. The wrapped error is given by `source()`, so
+    /// error reporters walking the chain print it once.
+    #[error("{context}")]
+    Context {
+        context: ErrorContext,
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// Classifies the error to decide whether the failed call is worth
+    /// retrying.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Code(code) => ErrorKind::from_code(*code),
+            Error::Unknown(_) => ErrorKind::Fatal,
+            Error::Desc(_) | Error::State(_) => ErrorKind::Configuration,
+            Error::Context { source, .. } => source.kind(),
+        }
+    }
+
+    /// Gets the SDK error code if the error comes from the SDK.
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            Error::Code(code) => Some(*code),
+            Error::Context { source, .. } => source.code(),
+            _ => None,
+        }
+    }
+
+    /// Gets the operation, camera and path the error occurred with.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        match self {
+            Error::Context { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// Attaches the context to the error. If the error already has one, the
+    /// two are merged and the fields of `context` take precedence.
+    pub(crate) fn with_context(self, context: ErrorContext) -> Self {
+        match self {
+            Error::Context {
+                context: inner,
+                source,
+            } => Error::Context {
+                context: context.merge(inner),
+                source,
+            },
+            err => Error::Context {
+                context,
+                source: Box::new(err),
+            },
+        }
+    }
+}
+
+/// The classification of an [Error].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// A transient failure, e.g. a disconnected camera or a saturated USB
+    /// bus. The call may succeed if retried, possibly after reopening.
+    Retryable,
+    /// The end of the SVO file is reached.
+    EndOfStream,
+    /// Invalid parameters, files or calls made by the caller.
+    Configuration,
+    /// The camera, sensors or GPU are not working or not supported.
+    Hardware,
+    /// The SDK installation or driver is broken, or the error is unknown.
+    Fatal,
+}
+
+impl ErrorKind {
+    pub fn from_code(code: ErrorCode) -> Self {
+        use ErrorCode as C;
+
+        match code {
+            C::SL_ERROR_CODE_CAMERA_NOT_DETECTED
+            | C::SL_ERROR_CODE_LOW_USB_BANDWIDTH
+            | C::SL_ERROR_CODE_CAMERA_DETECTION_ISSUE
+            | C::SL_ERROR_CODE_CANNOT_START_CAMERA_STREAM => ErrorKind::Retryable,
+            C::SL_ERROR_CODE_END_OF_SVOFILE_REACHED => ErrorKind::EndOfStream,
+            C::SL_ERROR_CODE_INVALID_RESOLUTION
+            | C::SL_ERROR_CODE_CALIBRATION_FILE_NOT_AVAILABLE
+            | C::SL_ERROR_CODE_INVALID_CALIBRATION_FILE
+            | C::SL_ERROR_CODE_INVALID_SVO_FILE
+            | C::SL_ERROR_CODE_SVO_UNSUPPORTED_COMPRESSION
+            | C::SL_ERROR_CODE_INVALID_COORDINATE_SYSTEM
+            | C::SL_ERROR_CODE_INVALID_FUNCTION_PARAMETERS
+            | C::SL_ERROR_CODE_INVALID_FUNCTION_CALL
+            | C::SL_ERROR_CODE_CAMERA_NOT_INITIALIZED
+            | C::SL_ERROR_CODE_INVALID_AREA_FILE
+            | C::SL_ERROR_CODE_INCOMPATIBLE_AREA_FILE
+            | C::SL_ERROR_CODE_MODULE_NOT_COMPATIBLE_WITH_CAMERA
+            | C::SL_ERROR_CODE_MOTION_SENSORS_REQUIRED
+            | C::SL_ERROR_CODE_PLANE_NOT_FOUND => ErrorKind::Configuration,
+            C::SL_ERROR_CODE_NO_GPU_COMPATIBLE
+            | C::SL_ERROR_CODE_NOT_ENOUGH_GPU_MEMORY
+            | C::SL_ERROR_CODE_SENSORS_NOT_INITIALIZED
+            | C::SL_ERROR_CODE_SENSORS_NOT_AVAILABLE
+            | C::SL_ERROR_CODE_SVO_RECORDING_ERROR
+            | C::SL_ERROR_CODE_INVALID_FIRMWARE
+            | C::SL_ERROR_CODE_CUDA_ERROR
+            | C::SL_ERROR_CODE_CAMERA_FAILED_TO_SETUP
+            | C::SL_ERROR_CODE_NO_GPU_DETECTED => ErrorKind::Hardware,
+            _ => ErrorKind::Fatal,
+        }
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        *self == ErrorKind::Retryable
+    }
+}
+
+/// The SDK call that failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Operation {
+    Open,
+    Grab,
+    EnableRecording,
+    EnableStreaming,
+    RetrieveImage,
+    RetrieveMeasure,
+    SaveImage,
+    SavePointCloud,
+    SetExposureRoi,
+    GetExposureRoi,
+    Reboot,
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Operation::Open => "open",
+            Operation::Grab => "grab",
+            Operation::EnableRecording => "enable_recording",
+            Operation::EnableStreaming => "enable_streaming",
+            Operation::RetrieveImage => "retrieve_image",
+            Operation::RetrieveMeasure => "retrieve_measure",
+            Operation::SaveImage => "save_current_image",
+            Operation::SavePointCloud => "save_point_cloud",
+            Operation::SetExposureRoi => "set_exposure_roi",
+            Operation::GetExposureRoi => "exposure_roi",
+            Operation::Reboot => "reboot",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// Where an [Error] occurred.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ErrorContext {
+    pub operation: Operation,
+    pub camera_id: Option<c_int>,
+    pub serial_number: Option<c_int>,
+    pub path: Option<PathBuf>,
+    pub addr: Option<SocketAddr>,
+}
+
+impl ErrorContext {
+    pub fn new(operation: Operation) -> Self {
+        Self {
+            operation,
+            camera_id: None,
+            serial_number: None,
+            path: None,
+            addr: None,
+        }
+    }
+
+    /// Fills the fields missing in `self` from `inner`. The operation of
+    /// `self` is kept.
+    fn merge(self, inner: ErrorContext) -> Self {
+        Self {
+            operation: self.operation,
+            camera_id: self.camera_id.or(inner.camera_id),
+            serial_number: self.serial_number.or(inner.serial_number),
+            path: self.path.or(inner.path),
+            addr: self.addr.or(inner.addr),
+        }
+    }
+}
+
+impl Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} failed", self.operation)?;
+
+        if let Some(id) = self.camera_id {
+            write!(f, " on camera {}", id)?;
+        }
+        if let Some(sn) = self.serial_number {
+            write!(f, " (serial number {})", sn)?;
+        }
+        if let Some(path) = &self.path {
+            write!(f, " with path {}", path.display())?;
+        }
+        if let Some(addr) = &self.addr {
+            write!(f, " with address {}", addr)?;
+        }
+
+        Ok(())
+    }
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
-pub(crate) fn code_to_result(code: u32) -> Result<()> {
-    let code: ErrorCode = unsafe { mem::transmute(code) };
+/// An invalid state transition or conversion of a camera.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
+pub enum StateError {
+    #[error("expect recording state {expect:?}, but the camera is in {found:?}")]
+    Recording {
+        expect: RecordingState,
+        found: RecordingState,
+    },
+    #[error("expect streaming state {expect:?}, but the camera is in {found:?}")]
+    Streaming {
+        expect: StreamingState,
+        found: StreamingState,
+    },
+    #[error("expect input type {expect:?}, but the camera has {found:?}")]
+    Input { expect: InputType, found: InputType },
+    #[error("the camera is still opened")]
+    Opened,
+}
+
+/// The step of the camera shutdown that failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CloseStep {
+    DisableStreaming,
+    DisableRecording,
+    CloseCamera,
+}
+
+impl Display for CloseStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            CloseStep::DisableStreaming => "disable streaming",
+            CloseStep::DisableRecording => "disable recording",
+            CloseStep::CloseCamera => "close camera",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+/// The error returned by [Camera::try_close](crate::Camera::try_close).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, thiserror::Error)]
+pub struct CloseError {
+    pub failures: Vec<(CloseStep, Error)>,
+}
+
+impl Display for CloseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unable to close camera")?;
+
+        for (step, err) in &self.failures {
+            write!(f, "; {} failed: {}", step, err)?;
+        }
 
-    match code {
-        sys::SL_ERROR_CODE::SL_ERROR_CODE_SUCCESS => Ok(()),
-        code => Err(Error::Code(code)),
+        Ok(())
+    }
+}
+
+pub(crate) fn code_to_result(code: c_int) -> Result<()> {
+    match ErrorCode::try_from(code) {
+        Ok(ErrorCode::SL_ERROR_CODE_SUCCESS) => Ok(()),
+        Ok(code) => Err(Error::Code(code)),
+        Err(code) => Err(Error::Unknown(code)),
     }
 }
 
@@ -27,3 +290,179 @@ macro_rules! ensure {
     };
 }
 pub(crate) use ensure;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+    use sys::{SL_CAMERA_STATE, SL_MODEL};
+
+    /// Collects the messages of the error and its sources, as printed by
+    /// error reporters.
+    fn chain(err: &Error) -> Vec<String> {
+        let mut messages = vec![err.to_string()];
+        let mut source = err.source();
+        while let Some(err) = source {
+            messages.push(err.to_string());
+            source = err.source();
+        }
+        messages
+    }
+
+    #[test]
+    fn convert_success_and_known_codes() {
+        assert_eq!(code_to_result(0), Ok(()));
+        assert_eq!(
+            code_to_result(ErrorCode::SL_ERROR_CODE_LOW_USB_BANDWIDTH as c_int),
+            Err(Error::Code(ErrorCode::SL_ERROR_CODE_LOW_USB_BANDWIDTH))
+        );
+    }
+
+    #[test]
+    fn convert_unknown_codes() {
+        assert_eq!(code_to_result(-1), Err(Error::Unknown(-1)));
+        assert_eq!(code_to_result(c_int::MIN), Err(Error::Unknown(c_int::MIN)));
+        assert_eq!(code_to_result(10_000), Err(Error::Unknown(10_000)));
+        assert_eq!(Error::Unknown(-1).kind(), ErrorKind::Fatal);
+    }
+
+    #[test]
+    fn error_code_round_trip() {
+        for code in [
+            ErrorCode::SL_ERROR_CODE_SUCCESS,
+            ErrorCode::SL_ERROR_CODE_CAMERA_NOT_DETECTED,
+            ErrorCode::SL_ERROR_CODE_MODULE_NOT_COMPATIBLE_WITH_CUDA_VERSION,
+        ] {
+            assert_eq!(ErrorCode::try_from(code as c_int), Ok(code));
+        }
+        assert_eq!(ErrorCode::try_from(-3), Err(-3));
+        assert_eq!(ErrorCode::try_from(c_int::MAX), Err(c_int::MAX));
+    }
+
+    #[test]
+    fn camera_state_round_trip() {
+        for state in [
+            SL_CAMERA_STATE::SL_CAMERA_STATE_AVAILABLE,
+            SL_CAMERA_STATE::SL_CAMERA_STATE_NOT_AVAILABLE,
+        ] {
+            assert_eq!(SL_CAMERA_STATE::try_from(state as c_int), Ok(state));
+        }
+        assert_eq!(SL_CAMERA_STATE::try_from(-1), Err(-1));
+        assert_eq!(SL_CAMERA_STATE::try_from(2), Err(2));
+    }
+
+    #[test]
+    fn model_round_trip() {
+        for model in [
+            SL_MODEL::SL_MODEL_ZED,
+            SL_MODEL::SL_MODEL_ZED_M,
+            SL_MODEL::SL_MODEL_ZED2,
+            SL_MODEL::SL_MODEL_ZED2i,
+        ] {
+            assert_eq!(SL_MODEL::try_from(model as c_int), Ok(model));
+        }
+        assert_eq!(SL_MODEL::try_from(-1), Err(-1));
+        assert_eq!(SL_MODEL::try_from(4), Err(4));
+    }
+
+    #[test]
+    fn classify_codes() {
+        use ErrorCode as C;
+
+        let kind = ErrorKind::from_code;
+        assert_eq!(
+            kind(C::SL_ERROR_CODE_CAMERA_NOT_DETECTED),
+            ErrorKind::Retryable
+        );
+        assert_eq!(
+            kind(C::SL_ERROR_CODE_LOW_USB_BANDWIDTH),
+            ErrorKind::Retryable
+        );
+        assert_eq!(
+            kind(C::SL_ERROR_CODE_END_OF_SVOFILE_REACHED),
+            ErrorKind::EndOfStream
+        );
+        assert_eq!(
+            kind(C::SL_ERROR_CODE_INVALID_SVO_FILE),
+            ErrorKind::Configuration
+        );
+        assert_eq!(
+            kind(C::SL_ERROR_CODE_PLANE_NOT_FOUND),
+            ErrorKind::Configuration
+        );
+        assert_eq!(kind(C::SL_ERROR_CODE_CUDA_ERROR), ErrorKind::Hardware);
+        assert_eq!(
+            kind(C::SL_ERROR_CODE_CORRUPTED_SDK_INSTALLATION),
+            ErrorKind::Fatal
+        );
+        assert!(!kind(C::SL_ERROR_CODE_PLANE_NOT_FOUND).is_retryable());
+    }
+
+    #[test]
+    fn wrap_error_with_context() {
+        let code = ErrorCode::SL_ERROR_CODE_CAMERA_NOT_DETECTED;
+        let context = ErrorContext {
+            camera_id: Some(1),
+            ..ErrorContext::new(Operation::Open)
+        };
+        let err = Error::Code(code).with_context(context.clone());
+
+        assert_eq!(err.context(), Some(&context));
+        assert_eq!(err.code(), Some(code));
+        assert_eq!(err.kind(), ErrorKind::Retryable);
+        assert_eq!(
+            chain(&err),
+            vec!["open failed on camera 1", "ZED error: CAMERA_NOT_DETECTED"]
+        );
+    }
+
+    #[test]
+    fn replace_context() {
+        let err = Error::Desc("bad".into())
+            .with_context(ErrorContext::new(Operation::Open))
+            .with_context(ErrorContext::new(Operation::Grab));
+
+        assert_eq!(err.context().unwrap().operation, Operation::Grab);
+        match &err {
+            Error::Context { source, .. } => assert_eq!(**source, Error::Desc("bad".into())),
+            err => panic!("expect a context error, but get {:?}", err),
+        }
+        assert_eq!(err.kind(), ErrorKind::Configuration);
+        assert_eq!(chain(&err), vec!["grab failed", "bad"]);
+    }
+
+    #[test]
+    fn merge_nested_contexts() {
+        let inner = ErrorContext {
+            camera_id: Some(0),
+            path: Some(PathBuf::from("/tmp/a.svo")),
+            ..ErrorContext::new(Operation::EnableRecording)
+        };
+        let outer = ErrorContext {
+            camera_id: Some(2),
+            serial_number: Some(1234),
+            ..ErrorContext::new(Operation::Open)
+        };
+        let err = Error::Code(ErrorCode::SL_ERROR_CODE_SVO_RECORDING_ERROR)
+            .with_context(inner)
+            .with_context(outer);
+
+        assert_eq!(
+            err.context(),
+            Some(&ErrorContext {
+                operation: Operation::Open,
+                camera_id: Some(2),
+                serial_number: Some(1234),
+                path: Some(PathBuf::from("/tmp/a.svo")),
+                addr: None,
+            })
+        );
+        assert_eq!(
+            chain(&err),
+            vec![
+                "open failed on camera 2 (serial number 1234) with path /tmp/a.svo",
+                "ZED error: SVO_RECORDING_ERROR",
+            ]
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{common::*, Error, ErrorContext, Operation, Result};
+use image::{codecs::jpeg::JpegEncoder, DynamicImage, ImageFormat};
+use std::{
+    fs::File,
+    io::BufWriter,
+    sync::mpsc::{self, TrySendError},
+    thread::{self, JoinHandle},
+};
+
+struct Job {
+    image: DynamicImage,
+    path: PathBuf,
+    format: ImageFormat,
+}
+
+/// Encodes and writes PNG or JPEG frames on a background thread, so the
+/// grab loop is not blocked by the disk.
+///
+/// Frames are queued in a bounded queue. The format is given by the file
+/// extension. Failures are reported through [try_recv_error](Self::try_recv_error).
+/// The queued frames are written before the encoder is dropped.
+#[derive(Debug)]
+pub struct FrameEncoder {
+    jobs: Option<mpsc::SyncSender<Job>>,
+    errors: mpsc::Receiver<Error>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl FrameEncoder {
+    /// Starts the encoder thread with a queue of `capacity` frames and the
+    /// JPEG quality from 1 to 100.
+    pub fn start(capacity: usize, jpeg_quality: u8) -> Self {
+        let (job_tx, job_rx) = mpsc::sync_channel::<Job>(capacity);
+        let (error_tx, error_rx) = mpsc::channel();
+
+        let handle = thread::spawn(move || {
+            for job in job_rx {
+                if let Err(err) = write_frame(&job, jpeg_quality) {
+                    let err = Error::Desc(err.to_string().into()).with_context(ErrorContext {
+                        path: Some(job.path),
+                        ..ErrorContext::new(Operation::SaveImage)
+                    });
+                    let _ = error_tx.send(err);
+                }
+            }
+        });
+
+        Self {
+            jobs: Some(job_tx),
+            errors: error_rx,
+            handle: Some(handle),
+        }
+    }
+
+    /// Queues the frame, blocking while the queue is full.
+    pub fn encode<I, P>(&self, image: I, path: P) -> Result<()>
+    where
+        I: Into<DynamicImage>,
+        P: AsRef<Path>,
+    {
+        let job = make_job(image.into(), path.as_ref())?;
+        self.sender()
+            .send(job)
+            .map_err(|_| Error::Desc("the encoder thread has stopped".into()))
+    }
+
+    /// Queues the frame if there is room, or drops it and returns `false`.
+    pub fn try_encode<I, P>(&self, image: I, path: P) -> Result<bool>
+    where
+        I: Into<DynamicImage>,
+        P: AsRef<Path>,
+    {
+        let job = make_job(image.into(), path.as_ref())?;
+        match self.sender().try_send(job) {
+            Ok(()) => Ok(true),
+            Err(TrySendError::Full(_)) => Ok(false),
+            Err(TrySendError::Disconnected(_)) => {
+                Err(Error::Desc("the encoder thread has stopped".into()))
+            }
+        }
+    }
+
+    /// Gets the next write failure if any, without blocking.
+    pub fn try_recv_error(&self) -> Option<Error> {
+        self.errors.try_recv().ok()
+    }
+
+    /// Writes the queued frames, stops the thread and returns the failures
+    /// not received yet.
+    pub fn finish(mut self) -> Vec<Error> {
+        self.join();
+        self.errors.try_iter().collect()
+    }
+
+    fn sender(&self) -> &mpsc::SyncSender<Job> {
+        self.jobs.as_ref().expect("the encoder is running")
+    }
+
+    fn join(&mut self) {
+        self.jobs = None;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for FrameEncoder {
+    fn drop(&mut self) {
+        self.join();
+    }
+}
+
+fn make_job(image: DynamicImage, path: &Path) -> Result<Job> {
+    let format = match ImageFormat::from_path(path) {
+        Ok(format @ (ImageFormat::Png | ImageFormat::Jpeg)) => format,
+        _ => {
+            return Err(Error::Desc(
+                format!("expect a .png or .jpg file, but get {}", path.display()).into(),
+            ))
+        }
+    };
+
+    Ok(Job {
+        image,
+        path: path.to_owned(),
+        format,
+    })
+}
+
+fn write_frame(job: &Job, jpeg_quality: u8) -> image::ImageResult<()> {
+    match job.format {
+        ImageFormat::Jpeg => {
+            // JPEG has no alpha nor 16-bit samples.
+            let image = match &job.image {
+                DynamicImage::ImageLuma8(_) | DynamicImage::ImageRgb8(_) => job.image.clone(),
+                image => DynamicImage::ImageRgb8(image.to_rgb8()),
+            };
+            let writer = BufWriter::new(File::create(&job.path)?);
+            let mut encoder = JpegEncoder::new_with_quality(writer, jpeg_quality.clamp(1, 100));
+            encoder.encode_image(&image)
+        }
+        format => job.image.save_with_format(&job.path, format),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::RgbaImage;
+    use std::{env, fs, process};
+
+    fn image() -> DynamicImage {
+        RgbaImage::from_pixel(4, 2, image::Rgba([10, 20, 30, 255])).into()
+    }
+
+    #[test]
+    fn accept_png_and_jpeg_paths() {
+        for path in ["a.png", "dir/b.jpg", "c.JPEG"] {
+            assert!(make_job(image(), Path::new(path)).is_ok(), "{}", path);
+        }
+        for path in ["a.bmp", "a.tiff", "frame", "a.png.tmp"] {
+            assert!(make_job(image(), Path::new(path)).is_err(), "{}", path);
+        }
+    }
+
+    #[test]
+    fn drop_frames_on_full_queue() {
+        // An encoder without a thread, so the queue is never drained.
+        let (jobs, _job_rx) = mpsc::sync_channel(1);
+        let (_error_tx, errors) = mpsc::channel();
+        let encoder = FrameEncoder {
+            jobs: Some(jobs),
+            errors,
+            handle: None,
+        };
+
+        assert_eq!(encoder.try_encode(image(), "a.png"), Ok(true));
+        assert_eq!(encoder.try_encode(image(), "b.png"), Ok(false));
+        assert!(encoder.try_encode(image(), "c.bmp").is_err());
+    }
+
+    #[test]
+    fn finish_writes_queued_frames() {
+        let dir = env::temp_dir().join(format!("zed-sdk-encoder-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let missing = dir.join("missing").join("c.png");
+
+        let encoder = FrameEncoder::start(4, 90);
+        encoder.encode(image(), dir.join("a.png")).unwrap();
+        encoder.encode(image(), dir.join("b.jpg")).unwrap();
+        encoder.encode(image(), &missing).unwrap();
+        let errors = encoder.finish();
+
+        assert!(image::open(dir.join("a.png")).is_ok());
+        assert!(image::open(dir.join("b.jpg")).is_ok());
+        assert_eq!(errors.len(), 1);
+        let context = errors[0].context().unwrap();
+        assert_eq!(context.operation, Operation::SaveImage);
+        assert_eq!(context.path.as_deref(), Some(missing.as_path()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
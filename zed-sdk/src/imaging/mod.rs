@@ -0,0 +1,122 @@
+mod encoder;
+pub use encoder::*;
+
+use crate::{
+    pixel_format::{PixelFormat, U8C1, U8C4},
+    DepthMap, Error, Mat, Result,
+};
+use image::{GrayImage, ImageBuffer, Luma, Pixel, RgbaImage};
+
+/// A 16-bit depth image in millimeters.
+pub type DepthImage = ImageBuffer<Luma<u16>, Vec<u16>>;
+
+impl Mat<U8C1> {
+    /// Copies the pixels into a grayscale image.
+    pub fn to_luma8(&self) -> Result<GrayImage> {
+        to_image_buffer(self, |&value, out| out.push(value))
+    }
+}
+
+impl Mat<U8C4> {
+    /// Copies the pixels into an RGBA image, keeping the channel order.
+    pub fn to_rgba8(&self) -> Result<RgbaImage> {
+        to_image_buffer(self, |pixel, out| out.extend_from_slice(pixel))
+    }
+
+    /// Copies the pixels of a BGRA image, as given by the SDK for color
+    /// views, into an RGBA image.
+    pub fn bgra_to_rgba8(&self) -> Result<RgbaImage> {
+        to_image_buffer(self, |&[b, g, r, a], out| {
+            out.extend_from_slice(&[r, g, b, a])
+        })
+    }
+}
+
+impl DepthMap {
+    /// Converts the depth into a 16-bit image in millimeters.
+    ///
+    /// Invalid depths are written as 0 and depths beyond 65.535 m are
+    /// clamped.
+    pub fn to_luma16_mm(&self) -> Result<DepthImage> {
+        let scale = self.unit().in_meters() * 1000.0;
+        to_image_buffer(self.mat(), |&value, out| {
+            out.push(depth_to_mm(value, scale))
+        })
+    }
+}
+
+fn depth_to_mm(value: f32, scale: f32) -> u16 {
+    // The validity is checked before scaling, since a huge finite depth may
+    // overflow to infinity.
+    if !value.is_finite() || value <= 0.0 {
+        return 0;
+    }
+    (value * scale).round().min(u16::MAX as f32) as u16
+}
+
+/// Copies the rows of the Mat into an image buffer, skipping the row
+/// padding.
+fn to_image_buffer<T, P, F>(mat: &Mat<T>, mut push: F) -> Result<ImageBuffer<P, Vec<P::Subpixel>>>
+where
+    T: PixelFormat,
+    P: Pixel,
+    F: FnMut(&T::Pixel, &mut Vec<P::Subpixel>),
+{
+    let (width, height) = mat.resolution();
+    let mut data = Vec::with_capacity(width * height * P::CHANNEL_COUNT as usize);
+    mat.pixels().for_each(|pixel| push(pixel, &mut data));
+
+    let (w, h) = match (u32::try_from(width), u32::try_from(height)) {
+        (Ok(w), Ok(h)) => (w, h),
+        _ => {
+            return Err(Error::Desc(
+                format!("Mat size {}x{} is too large for an image", width, height).into(),
+            ))
+        }
+    };
+    ImageBuffer::from_raw(w, h, data).ok_or(Error::Desc(
+        format!(
+            "unable to read the pixels of the Mat of size {}x{}",
+            width, height
+        )
+        .into(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Unit;
+
+    fn scale(unit: Unit) -> f32 {
+        unit.in_meters() * 1000.0
+    }
+
+    #[test]
+    fn scale_depth_to_mm() {
+        assert_eq!(depth_to_mm(1.5, scale(Unit::SL_UNIT_METER)), 1500);
+        assert_eq!(depth_to_mm(42.0, scale(Unit::SL_UNIT_CENTIMETER)), 420);
+        assert_eq!(depth_to_mm(1234.4, scale(Unit::SL_UNIT_MILLIMETER)), 1234);
+        assert_eq!(depth_to_mm(1234.6, scale(Unit::SL_UNIT_MILLIMETER)), 1235);
+        assert_eq!(depth_to_mm(10.0, scale(Unit::SL_UNIT_INCH)), 254);
+    }
+
+    #[test]
+    fn clamp_depth_to_u16() {
+        let scale = scale(Unit::SL_UNIT_METER);
+        assert_eq!(depth_to_mm(65.535, scale), u16::MAX);
+        assert_eq!(depth_to_mm(70.0, scale), u16::MAX);
+        assert_eq!(depth_to_mm(f32::MAX, scale), u16::MAX);
+    }
+
+    #[test]
+    fn write_invalid_depth_as_zero() {
+        let scale = scale(Unit::SL_UNIT_METER);
+        assert_eq!(depth_to_mm(f32::NAN, scale), 0);
+        assert_eq!(depth_to_mm(f32::INFINITY, scale), 0);
+        assert_eq!(depth_to_mm(f32::NEG_INFINITY, scale), 0);
+        assert_eq!(depth_to_mm(-0.5, scale), 0);
+        assert_eq!(depth_to_mm(0.0, scale), 0);
+        assert_eq!(depth_to_mm(0.0004, scale), 0);
+    }
+}
@@ -1,10 +1,15 @@
 use crate::{common::*, InputType};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Input<'a> {
     Svo(Cow<'a, Path>),
     Stream(SocketAddr),
     Usb(c_int),
+    /// A USB camera found by its serial number, which is stable across
+    /// replugs unlike the device index.
+    Serial(c_int),
 }
 
 impl<'a> Input<'a> {
@@ -29,11 +34,18 @@ impl<'a> Input<'a> {
         Self::Usb(<c_int as NumCast>::from(id).unwrap())
     }
 
+    pub fn serial<T>(sn: T) -> Self
+    where
+        T: ToPrimitive,
+    {
+        Self::Serial(<c_int as NumCast>::from(sn).unwrap())
+    }
+
     pub fn input_type(&self) -> InputType {
         match self {
             Input::Svo(_) => InputType::SL_INPUT_TYPE_SVO,
             Input::Stream(_) => InputType::SL_INPUT_TYPE_STREAM,
-            Input::Usb(_) => InputType::SL_INPUT_TYPE_USB,
+            Input::Usb(_) | Input::Serial(_) => InputType::SL_INPUT_TYPE_USB,
         }
     }
 }
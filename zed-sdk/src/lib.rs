@@ -1,19 +1,49 @@
+mod calibration;
 mod camera;
+mod camera_model;
 mod common;
+#[cfg(feature = "serde")]
+mod config;
 pub mod consts;
+mod device;
+mod device_monitor;
 pub mod error;
+#[cfg(feature = "image")]
+mod imaging;
 mod input;
+mod mat;
+mod measure;
+mod point_cloud;
+mod reboot;
 mod recording;
+mod rect;
+mod runtime_params;
 mod streaming_parameters;
+mod transform;
 mod utils;
 
 use crate::common::*;
+pub use calibration::*;
 pub use camera::*;
+pub use camera_model::*;
+#[cfg(feature = "serde")]
+pub use config::*;
 pub use consts::*;
+pub use device::*;
+pub use device_monitor::*;
 pub use error::*;
+#[cfg(feature = "image")]
+pub use imaging::*;
 pub use input::*;
+pub use mat::*;
+pub use measure::*;
+pub use point_cloud::*;
+pub use reboot::*;
 pub use recording::*;
+pub use rect::*;
+pub use runtime_params::*;
 pub use streaming_parameters::*;
+pub use transform::*;
 
 pub type ErrorCode = sys::SL_ERROR_CODE;
 pub type DeviceProperties = sys::SL_DeviceProperties;
@@ -22,24 +52,22 @@ pub type Resolution = sys::SL_RESOLUTION;
 pub type DepthMode = sys::SL_DEPTH_MODE;
 pub type InputType = sys::SL_INPUT_TYPE;
 pub type RuntimeParameters = sys::SL_RuntimeParameters;
+pub type SensingMode = sys::SL_SENSING_MODE;
 pub type Model = sys::SL_MODEL;
+pub type CameraState = sys::SL_CAMERA_STATE;
 pub type VideoSettings = sys::SL_VIDEO_SETTINGS;
+pub type Side = sys::SL_SIDE;
 pub type View = sys::SL_VIEW;
 pub type Mem = sys::SL_MEM;
 pub type CalibrationParameters = sys::SL_CalibrationParameters;
 pub type CameraParameters = sys::SL_CameraParameters;
 pub type FlipMode = sys::SL_FLIP_MODE;
+pub type Vector3 = sys::SL_Vector3;
 pub type Vector4 = sys::SL_Vector4;
+pub type Quaternion = sys::SL_Quaternion;
 pub type Unit = sys::SL_UNIT;
 pub type CoordinateSystem = sys::SL_COORDINATE_SYSTEM;
 pub type StreamingCodec = sys::SL_STREAMING_CODEC;
-
-pub fn get_device_list() -> Vec<DeviceProperties> {
-    unsafe {
-        let mut nb_devices: c_int = 0;
-        let mut device_list: Vec<sys::SL_DeviceProperties> = Vec::with_capacity(MAX_CAMERA_PLUGIN);
-        sys::sl_get_device_list(device_list.as_mut_ptr(), &mut nb_devices as *mut c_int);
-        device_list.set_len(nb_devices as usize);
-        mem::transmute(device_list)
-    }
-}
+pub type UsbDevice = sys::USB_DEVICE;
+pub type MatType = sys::SL_MAT_TYPE;
+pub type Measure = sys::SL_MEASURE;
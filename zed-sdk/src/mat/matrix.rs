@@ -0,0 +1,376 @@
+use super::pixel_format::PixelFormat;
+use crate::{
+    common::*,
+    ensure,
+    error::{code_to_result, Error},
+    Mem, Result,
+};
+use std::{ptr::NonNull, slice};
+
+/// A matrix of pixels allocated by the SDK in CPU memory.
+///
+/// Rows may be padded, so the pixels are accessed row by row with
+/// [row](Self::row) and [rows](Self::rows) rather than as one slice.
+///
+/// The memory layout is read from the SDK and checked against `T` once the
+/// Mat is allocated or retrieved into, so the pixel accessors do not call
+/// into the SDK.
+pub struct Mat<T>
+where
+    T: PixelFormat,
+{
+    ptr: NonNull<c_int>,
+    /// The layout cached by [sync_layout](Self::sync_layout), or `None` if
+    /// the memory is not allocated with the format `T`.
+    layout: Option<Layout>,
+    _phantom: PhantomData<T>,
+}
+
+// The Mat exclusively owns its memory, which is only mutated through
+// `&mut self`.
+unsafe impl<T: PixelFormat> Send for Mat<T> {}
+unsafe impl<T: PixelFormat> Sync for Mat<T> {}
+
+/// The checked memory layout of an allocated Mat.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Layout {
+    pub data: *mut u8,
+    pub width: usize,
+    pub height: usize,
+    pub step_bytes: usize,
+}
+
+impl Layout {
+    /// Checks that the memory can be read as rows of `P`, or returns `None`
+    /// if the pointer is null or misaligned, or the rows overlap.
+    pub fn new<P>(data: *mut u8, width: usize, height: usize, step_bytes: usize) -> Option<Self> {
+        let align = mem::align_of::<P>();
+        let valid = !data.is_null()
+            && data as usize % align == 0
+            && step_bytes % align == 0
+            && width
+                .checked_mul(mem::size_of::<P>())
+                .is_some_and(|row_bytes| step_bytes >= row_bytes);
+
+        valid.then_some(Self {
+            data,
+            width,
+            height,
+            step_bytes,
+        })
+    }
+
+    /// Gets the pixels of the row `y`, skipping the padding at its end.
+    ///
+    /// # Safety
+    /// The layout must be created with the same `P` over memory that
+    /// outlives `'a`, and `y` must be less than the height.
+    pub unsafe fn row_unchecked<'a, P>(&self, y: usize) -> &'a [P] {
+        let ptr = self.data.add(y * self.step_bytes) as *const P;
+        slice::from_raw_parts(ptr, self.width)
+    }
+
+    /// # Safety
+    /// Same as [row_unchecked](Self::row_unchecked), and no other reference
+    /// to the row may be alive during `'a`.
+    pub unsafe fn row_unchecked_mut<'a, P>(&self, y: usize) -> &'a mut [P] {
+        let ptr = self.data.add(y * self.step_bytes) as *mut P;
+        slice::from_raw_parts_mut(ptr, self.width)
+    }
+}
+
+impl<T> Mat<T>
+where
+    T: PixelFormat,
+{
+    /// Allocates a Mat with zeroed pixels.
+    pub fn new(width: usize, height: usize) -> Result<Self> {
+        ensure!(
+            width > 0 && height > 0,
+            "invalid Mat size {}x{}",
+            width,
+            height
+        );
+        let (c_width, c_height) = match (c_int::try_from(width), c_int::try_from(height)) {
+            (Ok(w), Ok(h)) => (w, h),
+            _ => {
+                return Err(Error::Desc(
+                    format!("Mat size {}x{} is too large", width, height).into(),
+                ))
+            }
+        };
+
+        let ptr =
+            unsafe { sys::sl_mat_create_new(c_width, c_height, T::MAT_TYPE, Mem::SL_MEM_CPU) };
+        let mut mat = Self::from_raw(ptr)?;
+        mat.sync_layout();
+        let layout = mat.layout().ok_or_else(|| {
+            Error::Desc(format!("unable to allocate Mat of size {}x{}", width, height).into())
+        })?;
+
+        // The SDK leaves the memory uninitialized.
+        for y in 0..layout.height {
+            unsafe {
+                let row = layout.data.add(y * layout.step_bytes) as *mut T::Pixel;
+                ptr::write_bytes(row, 0, layout.width);
+            }
+        }
+
+        Ok(mat)
+    }
+
+    /// Creates a Mat without memory, to be allocated by the SDK.
+    pub(crate) fn new_empty() -> Result<Self> {
+        let ptr = unsafe { sys::sl_mat_create_new_empty() };
+        Self::from_raw(ptr)
+    }
+
+    fn from_raw(ptr: *mut c_int) -> Result<Self> {
+        let ptr = NonNull::new(ptr).ok_or(Error::Desc("unable to create Mat".into()))?;
+        Ok(Self {
+            ptr,
+            layout: None,
+            _phantom: PhantomData,
+        })
+    }
+
+    pub(crate) fn as_raw(&self) -> *mut c_int {
+        self.ptr.as_ptr()
+    }
+
+    /// Checks whether the memory is allocated.
+    pub fn is_init(&self) -> bool {
+        unsafe { sys::sl_mat_is_init(self.as_raw()) }
+    }
+
+    pub fn width(&self) -> usize {
+        unsafe { sys::sl_mat_get_width(self.as_raw()).max(0) as usize }
+    }
+
+    pub fn height(&self) -> usize {
+        unsafe { sys::sl_mat_get_height(self.as_raw()).max(0) as usize }
+    }
+
+    pub fn resolution(&self) -> (usize, usize) {
+        (self.width(), self.height())
+    }
+
+    pub fn channels(&self) -> usize {
+        unsafe { sys::sl_mat_get_channels(self.as_raw()).max(0) as usize }
+    }
+
+    pub fn pixel_bytes(&self) -> usize {
+        unsafe { sys::sl_mat_get_pixel_bytes(self.as_raw()).max(0) as usize }
+    }
+
+    /// Gets the number of bytes between the starts of two rows.
+    pub fn step_bytes(&self) -> usize {
+        unsafe { sys::sl_mat_get_step_bytes(self.as_raw()).max(0) as usize }
+    }
+
+    /// Checks that the memory holds pixels of the format `T`.
+    ///
+    /// The SDK may reallocate the Mat with another format, e.g. when an
+    /// image of another view is retrieved into it.
+    pub fn check_format(&self) -> Result<()> {
+        ensure!(self.is_init(), "the Mat is not allocated");
+        ensure!(
+            self.channels() == T::CHANNELS && self.pixel_bytes() == mem::size_of::<T::Pixel>(),
+            "expect Mat with {} channels and {} bytes per pixel, but get {} channels and {} bytes",
+            T::CHANNELS,
+            mem::size_of::<T::Pixel>(),
+            self.channels(),
+            self.pixel_bytes()
+        );
+        Ok(())
+    }
+
+    pub(super) fn layout(&self) -> Option<Layout> {
+        self.layout
+    }
+
+    /// Reads the layout from the SDK again. It must be called after every
+    /// SDK call that may reallocate the memory.
+    pub(crate) fn sync_layout(&mut self) {
+        self.layout = self.check_format().ok().and_then(|()| {
+            let data = unsafe { sys::sl_mat_get_ptr(self.as_raw(), Mem::SL_MEM_CPU) as *mut u8 };
+            Layout::new::<T::Pixel>(data, self.width(), self.height(), self.step_bytes())
+        });
+    }
+
+    /// Gets the pixels of the row `y`, or `None` if `y` is out of bounds or
+    /// the Mat is not allocated.
+    pub fn row(&self, y: usize) -> Option<&[T::Pixel]> {
+        let layout = self.layout()?;
+        (y < layout.height).then(|| unsafe { self.row_unchecked(&layout, y) })
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> Option<&mut [T::Pixel]> {
+        let layout = self.layout()?;
+        if y >= layout.height {
+            return None;
+        }
+
+        Some(unsafe { layout.row_unchecked_mut(y) })
+    }
+
+    /// Iterates over the rows from top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[T::Pixel]> + '_ {
+        let rows = self.layout().map(|layout| {
+            (0..layout.height).map(move |y| unsafe { self.row_unchecked(&layout, y) })
+        });
+        rows.into_iter().flatten()
+    }
+
+    /// Iterates over the pixels in row-major order, skipping the row padding.
+    pub fn pixels(&self) -> impl Iterator<Item = &T::Pixel> + '_ {
+        self.rows().flatten()
+    }
+
+    /// Gets the row `y` with a layout computed once by the caller.
+    ///
+    /// # Safety
+    /// The layout must be the current [layout](Self::layout) of this Mat and
+    /// `y` must be less than its height.
+    unsafe fn row_unchecked(&self, layout: &Layout, y: usize) -> &[T::Pixel] {
+        layout.row_unchecked(y)
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<T::Pixel> {
+        self.row(y)?.get(x).copied()
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, value: T::Pixel) -> Result<()> {
+        let (width, height) = self
+            .layout
+            .map_or((0, 0), |layout| (layout.width, layout.height));
+        let pixel = self
+            .row_mut(y)
+            .and_then(|row| row.get_mut(x))
+            .ok_or_else(|| {
+                Error::Desc(
+                    format!(
+                        "pixel ({}, {}) is out of the Mat of size {}x{}",
+                        x, y, width, height
+                    )
+                    .into(),
+                )
+            })?;
+        *pixel = value;
+        Ok(())
+    }
+
+    /// Copies the pixels into a contiguous vector in row-major order.
+    pub fn to_vec(&self) -> Vec<T::Pixel> {
+        self.pixels().copied().collect()
+    }
+
+    /// Deep copies the pixels into `dst`, which is reallocated if its size
+    /// differs.
+    pub fn copy_to(&self, dst: &mut Mat<T>) -> Result<()> {
+        let code = unsafe {
+            sys::sl_mat_copy_to(
+                self.as_raw(),
+                dst.as_raw(),
+                sys::SL_COPY_TYPE_SL_COPY_TYPE_CPU_CPU,
+            )
+        };
+        dst.sync_layout();
+        code_to_result(code)
+    }
+
+    /// Deep copies the Mat, or returns an error if the SDK cannot create a
+    /// new one.
+    pub fn try_clone(&self) -> Result<Self> {
+        let mut mat = Self::new_empty()?;
+        unsafe {
+            sys::sl_mat_clone(mat.as_raw(), self.as_raw());
+        }
+        mat.sync_layout();
+        Ok(mat)
+    }
+}
+
+impl<T> Clone for Mat<T>
+where
+    T: PixelFormat,
+{
+    /// # Panics
+    /// Panics if the SDK cannot create a new Mat. Use
+    /// [try_clone](Mat::try_clone) to handle the failure.
+    fn clone(&self) -> Self {
+        self.try_clone().expect("unable to create Mat")
+    }
+}
+
+impl<T> fmt::Debug for Mat<T>
+where
+    T: PixelFormat,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mat")
+            .field("width", &self.width())
+            .field("height", &self.height())
+            .field("channels", &self.channels())
+            .field("step_bytes", &self.step_bytes())
+            .finish()
+    }
+}
+
+impl<T> Drop for Mat<T>
+where
+    T: PixelFormat,
+{
+    fn drop(&mut self) {
+        unsafe {
+            sys::sl_mat_free(self.as_raw(), Mem::SL_MEM_CPU);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reject_invalid_layouts() {
+        let mut buf = vec![0f32; 16];
+        let data = buf.as_mut_ptr() as *mut u8;
+
+        assert!(Layout::new::<f32>(data, 3, 2, 12).is_some());
+        assert!(Layout::new::<f32>(data, 3, 2, 16).is_some());
+
+        // null pointer
+        assert!(Layout::new::<f32>(ptr::null_mut(), 3, 2, 12).is_none());
+        // misaligned pointer
+        assert!(Layout::new::<f32>(unsafe { data.add(1) }, 3, 2, 12).is_none());
+        // misaligned step
+        assert!(Layout::new::<f32>(data, 3, 2, 14).is_none());
+        // step shorter than a row
+        assert!(Layout::new::<f32>(data, 3, 2, 8).is_none());
+        assert!(Layout::new::<[u8; 3]>(data, 4, 2, 11).is_none());
+        // row size overflows
+        assert!(Layout::new::<[f32; 4]>(data, usize::MAX / 2, 1, 16).is_none());
+    }
+
+    #[test]
+    fn skip_row_padding() {
+        // 3x2 pixels of 3 bytes in rows of 11 bytes
+        #[rustfmt::skip]
+        let mut buf: Vec<u8> = vec![
+            1, 2, 3,  4, 5, 6,  7, 8, 9,  0xee, 0xee,
+            10, 11, 12,  13, 14, 15,  16, 17, 18,  0xee, 0xee,
+        ];
+        let layout = Layout::new::<[u8; 3]>(buf.as_mut_ptr(), 3, 2, 11).unwrap();
+
+        let rows: Vec<&[[u8; 3]]> = (0..layout.height)
+            .map(|y| unsafe { layout.row_unchecked(y) })
+            .collect();
+        assert_eq!(rows[0], &[[1, 2, 3], [4, 5, 6], [7, 8, 9]]);
+        assert_eq!(rows[1], &[[10, 11, 12], [13, 14, 15], [16, 17, 18]]);
+
+        let row: &mut [[u8; 3]] = unsafe { layout.row_unchecked_mut(1) };
+        row[2] = [0; 3];
+        assert_eq!(&buf[16..22], &[15, 0, 0, 0, 0xee, 0xee][..]);
+    }
+}
@@ -0,0 +1,7 @@
+mod matrix;
+pub use matrix::*;
+
+#[cfg(feature = "ndarray")]
+mod ndarray;
+
+pub mod pixel_format;
@@ -0,0 +1,144 @@
+use super::{matrix::Layout, pixel_format::PixelFormat, Mat};
+use crate::{common::*, ensure, Error, Result};
+use ndarray::{Array2, Array3, ArrayView2, ArrayView3, ArrayViewMut2, ArrayViewMut3, ShapeBuilder};
+
+impl<T> Mat<T>
+where
+    T: PixelFormat,
+{
+    fn array_layout(&self) -> Result<Layout> {
+        self.layout()
+            .ok_or(Error::Desc("the Mat is not allocated".into()))
+    }
+
+    /// Borrows the pixels as a `(height, width)` array without copying.
+    ///
+    /// The row padding is skipped through the array strides. It fails if
+    /// the row step is not a whole number of pixels, which may happen with
+    /// 3-channel formats; use [as_array3](Self::as_array3) instead.
+    pub fn as_array2(&self) -> Result<ArrayView2<'_, T::Pixel>> {
+        let layout = self.array_layout()?;
+        unsafe { view2::<T>(&layout) }
+    }
+
+    pub fn as_array2_mut(&mut self) -> Result<ArrayViewMut2<'_, T::Pixel>> {
+        let layout = self.array_layout()?;
+        let shape = (layout.height, layout.width).strides(pixel_strides::<T>(&layout)?);
+        Ok(unsafe { ArrayViewMut2::from_shape_ptr(shape, layout.data as *mut T::Pixel) })
+    }
+
+    /// Borrows the pixels as a `(height, width, channels)` array without
+    /// copying.
+    pub fn as_array3(&self) -> Result<ArrayView3<'_, T::Channel>> {
+        let layout = self.array_layout()?;
+        Ok(unsafe { view3::<T>(&layout) })
+    }
+
+    pub fn as_array3_mut(&mut self) -> Result<ArrayViewMut3<'_, T::Channel>> {
+        let layout = self.array_layout()?;
+        let shape =
+            (layout.height, layout.width, T::CHANNELS).strides(channel_strides::<T>(&layout));
+        Ok(unsafe { ArrayViewMut3::from_shape_ptr(shape, layout.data as *mut T::Channel) })
+    }
+
+    /// Copies the pixels into an owned, contiguous `(height, width)` array.
+    pub fn to_array2(&self) -> Result<Array2<T::Pixel>> {
+        Ok(self.as_array2()?.to_owned())
+    }
+
+    /// Copies the pixels into an owned, contiguous
+    /// `(height, width, channels)` array.
+    pub fn to_array3(&self) -> Result<Array3<T::Channel>> {
+        Ok(self.as_array3()?.to_owned())
+    }
+}
+
+/// Borrows the memory of the layout as a `(height, width)` array.
+///
+/// # Safety
+/// The layout must be created for `T::Pixel` over memory that outlives `'a`.
+unsafe fn view2<'a, T: PixelFormat>(layout: &Layout) -> Result<ArrayView2<'a, T::Pixel>> {
+    let shape = (layout.height, layout.width).strides(pixel_strides::<T>(layout)?);
+    Ok(ArrayView2::from_shape_ptr(
+        shape,
+        layout.data as *const T::Pixel,
+    ))
+}
+
+/// Borrows the memory of the layout as a `(height, width, channels)` array.
+///
+/// # Safety
+/// Same as [view2].
+unsafe fn view3<'a, T: PixelFormat>(layout: &Layout) -> ArrayView3<'a, T::Channel> {
+    let shape = (layout.height, layout.width, T::CHANNELS).strides(channel_strides::<T>(layout));
+    ArrayView3::from_shape_ptr(shape, layout.data as *const T::Channel)
+}
+
+/// The strides in pixels of the rows and columns.
+fn pixel_strides<T: PixelFormat>(layout: &Layout) -> Result<(usize, usize)> {
+    let pixel_bytes = mem::size_of::<T::Pixel>();
+    ensure!(
+        layout.step_bytes % pixel_bytes == 0,
+        "row step of {} bytes is not a multiple of the pixel size {}",
+        layout.step_bytes,
+        pixel_bytes
+    );
+    Ok((layout.step_bytes / pixel_bytes, 1))
+}
+
+/// The strides in channels of the rows, columns and channels.
+fn channel_strides<T: PixelFormat>(layout: &Layout) -> (usize, usize, usize) {
+    (
+        layout.step_bytes / mem::size_of::<T::Channel>(),
+        T::CHANNELS,
+        1,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixel_format::{F32C3, U8C4};
+    use ndarray::Array;
+
+    #[test]
+    fn view_padded_u8c4() {
+        // 2x3 pixels in rows of 12 bytes, 4 of which are padding
+        #[rustfmt::skip]
+        let mut buf: Vec<u8> = vec![
+            1, 2, 3, 4,  5, 6, 7, 8,  0xee, 0xee, 0xee, 0xee,
+            9, 10, 11, 12,  13, 14, 15, 16,  0xee, 0xee, 0xee, 0xee,
+            17, 18, 19, 20,  21, 22, 23, 24,  0xee, 0xee, 0xee, 0xee,
+        ];
+        let layout = Layout::new::<[u8; 4]>(buf.as_mut_ptr(), 2, 3, 12).unwrap();
+        let unpadded: Vec<u8> = buf.chunks(12).flat_map(|row| &row[..8]).copied().collect();
+
+        let array2 = unsafe { view2::<U8C4>(&layout) }.unwrap();
+        assert_eq!(array2.dim(), (3, 2));
+        assert_eq!(array2[(1, 1)], [13, 14, 15, 16]);
+        assert_eq!(array2[(2, 0)], [17, 18, 19, 20]);
+
+        let array3 = unsafe { view3::<U8C4>(&layout) }.to_owned();
+        let expect = Array::from_shape_vec((3, 2, 4), unpadded).unwrap();
+        assert_eq!(array3, expect);
+    }
+
+    #[test]
+    fn view_padded_f32c3() {
+        // 2x2 pixels of 12 bytes in rows of 28 bytes, which is not a whole
+        // number of pixels
+        #[rustfmt::skip]
+        let mut buf: Vec<f32> = vec![
+            1.0, 2.0, 3.0,  4.0, 5.0, 6.0,  -1.0,
+            7.0, 8.0, 9.0,  10.0, 11.0, 12.0,  -1.0,
+        ];
+        let layout = Layout::new::<[f32; 3]>(buf.as_mut_ptr() as *mut u8, 2, 2, 28).unwrap();
+
+        assert!(unsafe { view2::<F32C3>(&layout) }.is_err());
+
+        let array3 = unsafe { view3::<F32C3>(&layout) }.to_owned();
+        let unpadded: Vec<f32> = buf.chunks(7).flat_map(|row| &row[..6]).copied().collect();
+        let expect = Array::from_shape_vec((2, 2, 3), unpadded).unwrap();
+        assert_eq!(array3, expect);
+    }
+}
@@ -0,0 +1,50 @@
+use crate::{common::*, utils::declare_marker, MatType};
+
+declare_marker!(pub U8C1);
+declare_marker!(pub U8C2);
+declare_marker!(pub U8C3);
+declare_marker!(pub U8C4);
+declare_marker!(pub U16C1);
+declare_marker!(pub F32C1);
+declare_marker!(pub F32C2);
+declare_marker!(pub F32C3);
+declare_marker!(pub F32C4);
+
+/// Marker types of the [Mat](crate::Mat) pixel format.
+///
+/// It is sealed since the pixel memory is reinterpreted as `Pixel`.
+pub trait PixelFormat: private::Sealed {
+    /// The value of one pixel.
+    type Pixel: Copy + Default + fmt::Debug + PartialEq + Send + Sync + 'static;
+    /// The channel type, e.g. `u8` for `[u8; 4]` pixels.
+    type Channel: Copy + Default + fmt::Debug + PartialEq + Send + Sync + 'static;
+    const MAT_TYPE: MatType;
+    const CHANNELS: usize;
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+macro_rules! impl_pixel_format {
+    ($marker:ident, $pixel:ty, $channel:ty, $channels:expr, $mat_type:ident) => {
+        impl private::Sealed for $marker {}
+
+        impl PixelFormat for $marker {
+            type Pixel = $pixel;
+            type Channel = $channel;
+            const MAT_TYPE: MatType = sys::$mat_type;
+            const CHANNELS: usize = $channels;
+        }
+    };
+}
+
+impl_pixel_format!(U8C1, u8, u8, 1, SL_MAT_TYPE_SL_MAT_TYPE_U8_C1);
+impl_pixel_format!(U8C2, [u8; 2], u8, 2, SL_MAT_TYPE_SL_MAT_TYPE_U8_C2);
+impl_pixel_format!(U8C3, [u8; 3], u8, 3, SL_MAT_TYPE_SL_MAT_TYPE_U8_C3);
+impl_pixel_format!(U8C4, [u8; 4], u8, 4, SL_MAT_TYPE_SL_MAT_TYPE_U8_C4);
+impl_pixel_format!(U16C1, u16, u16, 1, SL_MAT_TYPE_SL_MAT_TYPE_U16_C1);
+impl_pixel_format!(F32C1, f32, f32, 1, SL_MAT_TYPE_SL_MAT_TYPE_F32_C1);
+impl_pixel_format!(F32C2, [f32; 2], f32, 2, SL_MAT_TYPE_SL_MAT_TYPE_F32_C2);
+impl_pixel_format!(F32C3, [f32; 3], f32, 3, SL_MAT_TYPE_SL_MAT_TYPE_F32_C3);
+impl_pixel_format!(F32C4, [f32; 4], f32, 4, SL_MAT_TYPE_SL_MAT_TYPE_F32_C4);
@@ -0,0 +1,478 @@
+use crate::{
+    common::*,
+    pixel_format::{PixelFormat, F32C1, F32C4},
+    Mat, Measure, Side, Unit,
+};
+
+/// A buffer of a measure retrieved by
+/// [GrabHandle::retrieve_measure](crate::GrabHandle::retrieve_measure).
+pub trait MeasureBuffer: Sized + private::Sealed {
+    type Format: PixelFormat;
+
+    /// Checks whether the buffer can hold the measure.
+    fn accepts(measure: Measure) -> bool;
+
+    #[doc(hidden)]
+    fn from_mat(mat: Mat<Self::Format>, measure: Measure, unit: Unit) -> Self;
+}
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A depth or distance value, with the invalid values given by the SDK
+/// told apart.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DepthValue {
+    Valid(f32),
+    /// The depth cannot be estimated, e.g. in occluded areas. It is NaN
+    /// in the raw buffer.
+    Unknown,
+    /// Closer than the minimum depth distance. It is -inf in the raw
+    /// buffer.
+    TooClose,
+    /// Farther than the maximum depth distance. It is +inf in the raw
+    /// buffer.
+    TooFar,
+}
+
+impl DepthValue {
+    pub fn from_raw(value: f32) -> Self {
+        if value.is_nan() {
+            DepthValue::Unknown
+        } else if value == f32::NEG_INFINITY {
+            DepthValue::TooClose
+        } else if value == f32::INFINITY {
+            DepthValue::TooFar
+        } else {
+            DepthValue::Valid(value)
+        }
+    }
+
+    pub fn valid(&self) -> Option<f32> {
+        match *self {
+            DepthValue::Valid(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// The channel order of the color packed in the 4th float of a
+/// [PointCloud].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorOrder {
+    Rgba,
+    Bgra,
+    Argb,
+    Abgr,
+}
+
+impl ColorOrder {
+    fn from_measure(measure: Measure) -> Option<Self> {
+        use Measure as M;
+
+        let order = match measure {
+            M::SL_MEASURE_XYZRGBA | M::SL_MEASURE_XYZRGBA_RIGHT => ColorOrder::Rgba,
+            M::SL_MEASURE_XYZBGRA | M::SL_MEASURE_XYZBGRA_RIGHT => ColorOrder::Bgra,
+            M::SL_MEASURE_XYZARGB | M::SL_MEASURE_XYZARGB_RIGHT => ColorOrder::Argb,
+            M::SL_MEASURE_XYZABGR | M::SL_MEASURE_XYZABGR_RIGHT => ColorOrder::Abgr,
+            _ => return None,
+        };
+        Some(order)
+    }
+}
+
+fn side_of(measure: Measure) -> Side {
+    if measure.is_right() {
+        Side::SL_SIDE_RIGHT
+    } else {
+        Side::SL_SIDE_LEFT
+    }
+}
+
+fn finite<const N: usize>(values: [f32; N]) -> Option<[f32; N]> {
+    values.iter().all(|v| v.is_finite()).then_some(values)
+}
+
+fn valid_range(values: impl IntoIterator<Item = DepthValue>) -> Option<(f32, f32)> {
+    values
+        .into_iter()
+        .filter_map(|value| value.valid())
+        .fold(None, |range, value| match range {
+            None => Some((value, value)),
+            Some((min, max)) => Some((min.min(value), max.max(value))),
+        })
+}
+
+/// Implements the accessors shared by the measure buffers.
+macro_rules! impl_measure_common {
+    ($name:ident, $format:ident) => {
+        impl $name {
+            pub fn width(&self) -> usize {
+                self.mat.width()
+            }
+
+            pub fn height(&self) -> usize {
+                self.mat.height()
+            }
+
+            pub fn resolution(&self) -> (usize, usize) {
+                self.mat.resolution()
+            }
+
+            pub fn side(&self) -> Side {
+                self.side
+            }
+
+            pub fn mat(&self) -> &Mat<$format> {
+                &self.mat
+            }
+
+            pub fn into_mat(self) -> Mat<$format> {
+                self.mat
+            }
+        }
+
+        impl private::Sealed for $name {}
+    };
+}
+
+/// The distance along the optical axis for each pixel, in the coordinate
+/// unit of the camera.
+#[derive(Debug, Clone)]
+pub struct DepthMap {
+    mat: Mat<F32C1>,
+    unit: Unit,
+    side: Side,
+}
+
+impl_measure_common!(DepthMap, F32C1);
+
+impl DepthMap {
+    pub fn unit(&self) -> Unit {
+        self.unit
+    }
+
+    /// Gets the depth of the pixel, or `None` if it is out of bounds.
+    pub fn get(&self, x: usize, y: usize) -> Option<DepthValue> {
+        self.mat.get(x, y).map(DepthValue::from_raw)
+    }
+
+    /// Gets the depth of the pixel if it is valid.
+    pub fn get_valid(&self, x: usize, y: usize) -> Option<f32> {
+        self.get(x, y)?.valid()
+    }
+
+    /// Iterates over the depth values in row-major order.
+    pub fn values(&self) -> impl Iterator<Item = DepthValue> + '_ {
+        self.mat.pixels().map(|&value| DepthValue::from_raw(value))
+    }
+
+    /// Gets the smallest and largest valid depth, or `None` if no pixel is
+    /// valid.
+    pub fn valid_range(&self) -> Option<(f32, f32)> {
+        valid_range(self.values())
+    }
+}
+
+impl MeasureBuffer for DepthMap {
+    type Format = F32C1;
+
+    fn accepts(measure: Measure) -> bool {
+        matches!(
+            measure,
+            Measure::SL_MEASURE_DEPTH | Measure::SL_MEASURE_DEPTH_RIGHT
+        )
+    }
+
+    fn from_mat(mat: Mat<F32C1>, measure: Measure, unit: Unit) -> Self {
+        Self {
+            mat,
+            unit,
+            side: side_of(measure),
+        }
+    }
+}
+
+/// The confidence of the depth for each pixel, from 1 (most confident) to
+/// 100 (least confident).
+#[derive(Debug, Clone)]
+pub struct ConfidenceMap {
+    mat: Mat<F32C1>,
+    side: Side,
+}
+
+impl_measure_common!(ConfidenceMap, F32C1);
+
+impl ConfidenceMap {
+    /// Gets the confidence of the pixel, or `None` if it is out of bounds
+    /// or not finite.
+    pub fn get(&self, x: usize, y: usize) -> Option<f32> {
+        self.mat.get(x, y).filter(|value| value.is_finite())
+    }
+}
+
+impl MeasureBuffer for ConfidenceMap {
+    type Format = F32C1;
+
+    fn accepts(measure: Measure) -> bool {
+        measure == Measure::SL_MEASURE_CONFIDENCE
+    }
+
+    fn from_mat(mat: Mat<F32C1>, measure: Measure, _unit: Unit) -> Self {
+        Self {
+            mat,
+            side: side_of(measure),
+        }
+    }
+}
+
+/// The disparity in pixels between the left and right images.
+#[derive(Debug, Clone)]
+pub struct DisparityMap {
+    mat: Mat<F32C1>,
+    side: Side,
+}
+
+impl_measure_common!(DisparityMap, F32C1);
+
+impl DisparityMap {
+    /// Gets the disparity of the pixel, or `None` if it is out of bounds or
+    /// invalid.
+    pub fn get(&self, x: usize, y: usize) -> Option<f32> {
+        self.mat.get(x, y).filter(|value| value.is_finite())
+    }
+}
+
+impl MeasureBuffer for DisparityMap {
+    type Format = F32C1;
+
+    fn accepts(measure: Measure) -> bool {
+        matches!(
+            measure,
+            Measure::SL_MEASURE_DISPARITY | Measure::SL_MEASURE_DISPARITY_RIGHT
+        )
+    }
+
+    fn from_mat(mat: Mat<F32C1>, measure: Measure, _unit: Unit) -> Self {
+        Self {
+            mat,
+            side: side_of(measure),
+        }
+    }
+}
+
+/// The 3D position of each pixel in the coordinate unit of the camera,
+/// optionally with the color packed in the 4th channel.
+#[derive(Debug, Clone)]
+pub struct PointCloud {
+    mat: Mat<F32C4>,
+    unit: Unit,
+    side: Side,
+    color_order: Option<ColorOrder>,
+}
+
+impl_measure_common!(PointCloud, F32C4);
+
+impl PointCloud {
+    pub fn unit(&self) -> Unit {
+        self.unit
+    }
+
+    /// Gets the channel order of the packed color, or `None` if the point
+    /// cloud has no color.
+    pub fn color_order(&self) -> Option<ColorOrder> {
+        self.color_order
+    }
+
+    /// Gets the position of the pixel, or `None` if it is out of bounds or
+    /// any coordinate is NaN or infinite.
+    pub fn position(&self, x: usize, y: usize) -> Option<[f32; 3]> {
+        let [px, py, pz, _] = self.mat.get(x, y)?;
+        finite([px, py, pz])
+    }
+
+    /// Gets the color bytes of the pixel in the [color_order](Self::color_order).
+    pub fn packed_color(&self, x: usize, y: usize) -> Option<[u8; 4]> {
+        self.color_order?;
+        let [_, _, _, color] = self.mat.get(x, y)?;
+        Some(color.to_ne_bytes())
+    }
+}
+
+impl MeasureBuffer for PointCloud {
+    type Format = F32C4;
+
+    fn accepts(measure: Measure) -> bool {
+        measure.mat_type() == sys::SL_MAT_TYPE_SL_MAT_TYPE_F32_C4
+            && !matches!(
+                measure,
+                Measure::SL_MEASURE_NORMALS | Measure::SL_MEASURE_NORMALS_RIGHT
+            )
+    }
+
+    fn from_mat(mat: Mat<F32C4>, measure: Measure, unit: Unit) -> Self {
+        Self {
+            mat,
+            unit,
+            side: side_of(measure),
+            color_order: ColorOrder::from_measure(measure),
+        }
+    }
+}
+
+/// The unit normal vector of the surface at each pixel.
+#[derive(Debug, Clone)]
+pub struct NormalMap {
+    mat: Mat<F32C4>,
+    side: Side,
+}
+
+impl_measure_common!(NormalMap, F32C4);
+
+impl NormalMap {
+    /// Gets the normal of the pixel, or `None` if it is out of bounds or
+    /// invalid.
+    pub fn get(&self, x: usize, y: usize) -> Option<[f32; 3]> {
+        let [nx, ny, nz, _] = self.mat.get(x, y)?;
+        finite([nx, ny, nz])
+    }
+}
+
+impl MeasureBuffer for NormalMap {
+    type Format = F32C4;
+
+    fn accepts(measure: Measure) -> bool {
+        matches!(
+            measure,
+            Measure::SL_MEASURE_NORMALS | Measure::SL_MEASURE_NORMALS_RIGHT
+        )
+    }
+
+    fn from_mat(mat: Mat<F32C4>, measure: Measure, _unit: Unit) -> Self {
+        Self {
+            mat,
+            side: side_of(measure),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_MEASURES: [Measure; 19] = [
+        Measure::SL_MEASURE_DISPARITY,
+        Measure::SL_MEASURE_DEPTH,
+        Measure::SL_MEASURE_CONFIDENCE,
+        Measure::SL_MEASURE_XYZ,
+        Measure::SL_MEASURE_XYZRGBA,
+        Measure::SL_MEASURE_XYZBGRA,
+        Measure::SL_MEASURE_XYZARGB,
+        Measure::SL_MEASURE_XYZABGR,
+        Measure::SL_MEASURE_NORMALS,
+        Measure::SL_MEASURE_DISPARITY_RIGHT,
+        Measure::SL_MEASURE_DEPTH_RIGHT,
+        Measure::SL_MEASURE_XYZ_RIGHT,
+        Measure::SL_MEASURE_XYZRGBA_RIGHT,
+        Measure::SL_MEASURE_XYZBGRA_RIGHT,
+        Measure::SL_MEASURE_XYZARGB_RIGHT,
+        Measure::SL_MEASURE_XYZABGR_RIGHT,
+        Measure::SL_MEASURE_NORMALS_RIGHT,
+        Measure::SL_MEASURE_DEPTH_U16_MM,
+        Measure::SL_MEASURE_DEPTH_U16_MM_RIGHT,
+    ];
+
+    fn accepted<B: MeasureBuffer>() -> Vec<Measure> {
+        ALL_MEASURES
+            .iter()
+            .copied()
+            .filter(|&measure| B::accepts(measure))
+            .collect()
+    }
+
+    #[test]
+    fn classify_raw_depth() {
+        assert_eq!(DepthValue::from_raw(f32::NAN), DepthValue::Unknown);
+        assert_eq!(
+            DepthValue::from_raw(f32::NEG_INFINITY),
+            DepthValue::TooClose
+        );
+        assert_eq!(DepthValue::from_raw(f32::INFINITY), DepthValue::TooFar);
+        assert_eq!(DepthValue::from_raw(1.5), DepthValue::Valid(1.5));
+        assert_eq!(DepthValue::from_raw(0.0), DepthValue::Valid(0.0));
+
+        assert_eq!(DepthValue::from_raw(2.0).valid(), Some(2.0));
+        assert_eq!(DepthValue::from_raw(f32::NAN).valid(), None);
+        assert_eq!(DepthValue::from_raw(f32::INFINITY).valid(), None);
+    }
+
+    #[test]
+    fn valid_depth_range() {
+        let raw = [f32::NAN, 3.0, f32::NEG_INFINITY, 0.5, f32::INFINITY, 2.0];
+        assert_eq!(
+            valid_range(raw.iter().copied().map(DepthValue::from_raw)),
+            Some((0.5, 3.0))
+        );
+
+        let invalid = [f32::NAN, f32::NEG_INFINITY, f32::INFINITY];
+        assert_eq!(
+            valid_range(invalid.iter().copied().map(DepthValue::from_raw)),
+            None
+        );
+        assert_eq!(valid_range([]), None);
+    }
+
+    #[test]
+    fn buffers_accept_measures() {
+        use Measure as M;
+
+        assert_eq!(
+            accepted::<DepthMap>(),
+            vec![M::SL_MEASURE_DEPTH, M::SL_MEASURE_DEPTH_RIGHT]
+        );
+        assert_eq!(accepted::<ConfidenceMap>(), vec![M::SL_MEASURE_CONFIDENCE]);
+        assert_eq!(
+            accepted::<DisparityMap>(),
+            vec![M::SL_MEASURE_DISPARITY, M::SL_MEASURE_DISPARITY_RIGHT]
+        );
+        assert_eq!(
+            accepted::<PointCloud>(),
+            vec![
+                M::SL_MEASURE_XYZ,
+                M::SL_MEASURE_XYZRGBA,
+                M::SL_MEASURE_XYZBGRA,
+                M::SL_MEASURE_XYZARGB,
+                M::SL_MEASURE_XYZABGR,
+                M::SL_MEASURE_XYZ_RIGHT,
+                M::SL_MEASURE_XYZRGBA_RIGHT,
+                M::SL_MEASURE_XYZBGRA_RIGHT,
+                M::SL_MEASURE_XYZARGB_RIGHT,
+                M::SL_MEASURE_XYZABGR_RIGHT,
+            ]
+        );
+        assert_eq!(
+            accepted::<NormalMap>(),
+            vec![M::SL_MEASURE_NORMALS, M::SL_MEASURE_NORMALS_RIGHT]
+        );
+    }
+
+    #[test]
+    fn measure_side_and_color_order() {
+        use Measure as M;
+
+        assert_eq!(side_of(M::SL_MEASURE_DEPTH), Side::SL_SIDE_LEFT);
+        assert_eq!(side_of(M::SL_MEASURE_DEPTH_RIGHT), Side::SL_SIDE_RIGHT);
+        assert_eq!(side_of(M::SL_MEASURE_NORMALS_RIGHT), Side::SL_SIDE_RIGHT);
+
+        assert_eq!(ColorOrder::from_measure(M::SL_MEASURE_XYZ), None);
+        assert_eq!(
+            ColorOrder::from_measure(M::SL_MEASURE_XYZBGRA_RIGHT),
+            Some(ColorOrder::Bgra)
+        );
+        assert_eq!(
+            ColorOrder::from_measure(M::SL_MEASURE_XYZABGR),
+            Some(ColorOrder::Abgr)
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use super::{ColoredPoint, PointCloudView};
+use crate::{common::*, Error, ErrorContext, Operation, Result};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+};
+
+/// The file format written by [PointCloudView::save].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PointCloudFormat {
+    PlyAscii,
+    PlyBinary,
+    PcdAscii,
+    PcdBinary,
+}
+
+impl<'a> PointCloudView<'a> {
+    /// Writes the valid points to the file and returns the number of
+    /// points written.
+    pub fn save<P>(&self, path: P, format: PointCloudFormat) -> Result<usize>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let result = File::create(path).and_then(|file| {
+            let mut writer = BufWriter::new(file);
+            let count = self.write_to(&mut writer, format)?;
+            writer.flush()?;
+            Ok(count)
+        });
+
+        result.map_err(|err| {
+            Error::Desc(err.to_string().into()).with_context(ErrorContext {
+                path: Some(path.to_owned()),
+                ..ErrorContext::new(Operation::SavePointCloud)
+            })
+        })
+    }
+
+    /// Writes the valid points to the writer and returns the number of
+    /// points written.
+    ///
+    /// Points are written as `float` coordinates with an RGBA color. PCD
+    /// files pack the color in an `rgba` field as PCL does.
+    pub fn write_to<W>(&self, mut writer: W, format: PointCloudFormat) -> io::Result<usize>
+    where
+        W: Write,
+    {
+        let points: Vec<ColoredPoint> = self.iter().collect();
+
+        match format {
+            PointCloudFormat::PlyAscii => {
+                write_ply_header(&mut writer, "ascii", points.len())?;
+                for &(x, y, z, [r, g, b, a]) in &points {
+                    writeln!(writer, "{} {} {} {} {} {} {}", x, y, z, r, g, b, a)?;
+                }
+            }
+            PointCloudFormat::PlyBinary => {
+                write_ply_header(&mut writer, "binary_little_endian", points.len())?;
+                for &(x, y, z, color) in &points {
+                    write_xyz(&mut writer, [x, y, z])?;
+                    writer.write_all(&color)?;
+                }
+            }
+            PointCloudFormat::PcdAscii => {
+                write_pcd_header(&mut writer, "ascii", points.len())?;
+                for &(x, y, z, color) in &points {
+                    writeln!(writer, "{} {} {} {}", x, y, z, pcl_rgba(color))?;
+                }
+            }
+            PointCloudFormat::PcdBinary => {
+                write_pcd_header(&mut writer, "binary", points.len())?;
+                for &(x, y, z, color) in &points {
+                    write_xyz(&mut writer, [x, y, z])?;
+                    writer.write_all(&pcl_rgba(color).to_le_bytes())?;
+                }
+            }
+        }
+
+        Ok(points.len())
+    }
+}
+
+fn write_ply_header<W: Write>(writer: &mut W, format: &str, count: usize) -> io::Result<()> {
+    writeln!(writer, "ply")?;
+    writeln!(writer, "format {} 1.0", format)?;
+    writeln!(writer, "element vertex {}", count)?;
+    for name in ["x", "y", "z"] {
+        writeln!(writer, "property float {}", name)?;
+    }
+    for name in ["red", "green", "blue", "alpha"] {
+        writeln!(writer, "property uchar {}", name)?;
+    }
+    writeln!(writer, "end_header")
+}
+
+fn write_pcd_header<W: Write>(writer: &mut W, data: &str, count: usize) -> io::Result<()> {
+    writeln!(writer, "# .PCD v0.7 - Point Cloud Data file format")?;
+    writeln!(writer, "VERSION 0.7")?;
+    writeln!(writer, "FIELDS x y z rgba")?;
+    writeln!(writer, "SIZE 4 4 4 4")?;
+    writeln!(writer, "TYPE F F F U")?;
+    writeln!(writer, "COUNT 1 1 1 1")?;
+    writeln!(writer, "WIDTH {}", count)?;
+    writeln!(writer, "HEIGHT 1")?;
+    writeln!(writer, "VIEWPOINT 0 0 0 1 0 0 0")?;
+    writeln!(writer, "POINTS {}", count)?;
+    writeln!(writer, "DATA {}", data)
+}
+
+fn write_xyz<W: Write>(writer: &mut W, xyz: [f32; 3]) -> io::Result<()> {
+    for value in xyz {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Packs the color as PCL does, i.e. `0xAARRGGBB`.
+fn pcl_rgba([r, g, b, a]: [u8; 4]) -> u32 {
+    u32::from_be_bytes([a, r, g, b])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ColorOrder, Transform};
+
+    fn view(points: &[[f32; 4]]) -> PointCloudView<'_> {
+        PointCloudView::from_slice(points, points.len(), ColorOrder::Rgba).unwrap()
+    }
+
+    fn write(view: &PointCloudView<'_>, format: PointCloudFormat) -> Vec<u8> {
+        let mut buf = vec![];
+        view.write_to(&mut buf, format).unwrap();
+        buf
+    }
+
+    #[test]
+    fn ply_ascii_drops_nan_points() {
+        let color = f32::from_ne_bytes([10, 20, 30, 255]);
+        let points = [[1.0, 2.5, -3.0, color], [f32::NAN, 0.0, 0.0, color]];
+        let text = String::from_utf8(write(&view(&points), PointCloudFormat::PlyAscii)).unwrap();
+
+        assert!(text.starts_with("ply\nformat ascii 1.0\nelement vertex 1\n"));
+        assert!(text.ends_with("end_header\n1 2.5 -3 10 20 30 255\n"));
+    }
+
+    #[test]
+    fn ply_binary_layout() {
+        let color = f32::from_ne_bytes([1, 2, 3, 4]);
+        let points = [[1.0, 2.0, 3.0, color]];
+        let buf = write(&view(&points), PointCloudFormat::PlyBinary);
+
+        let header = b"end_header\n";
+        let start = buf.windows(header.len()).position(|w| w == header).unwrap() + header.len();
+        let body = &buf[start..];
+        assert_eq!(body.len(), 16);
+        assert_eq!(&body[..4], &1.0f32.to_le_bytes());
+        assert_eq!(&body[8..12], &3.0f32.to_le_bytes());
+        assert_eq!(&body[12..], &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn pcd_ascii_with_transform() {
+        let color = f32::from_ne_bytes([0xff, 0x80, 0x00, 0xff]);
+        let points = [[1.0, 0.0, 0.0, color], [0.0, f32::INFINITY, 0.0, color]];
+        let transform = Transform {
+            translation: [0.0, 0.0, 2.0],
+            ..Transform::IDENTITY
+        };
+        let view = view(&points).transform(transform);
+        let text = String::from_utf8(write(&view, PointCloudFormat::PcdAscii)).unwrap();
+
+        assert!(text.contains("\nWIDTH 1\n"));
+        assert!(text.contains("\nPOINTS 1\nDATA ascii\n"));
+        assert!(text.ends_with(&format!("1 0 2 {}\n", 0xffff8000u32)));
+    }
+
+    #[test]
+    fn pcd_binary_size() {
+        let points = [[1.0, 2.0, 3.0, 0.0]; 3];
+        let buf = write(&view(&points), PointCloudFormat::PcdBinary);
+        let text = String::from_utf8_lossy(&buf);
+        let start = text.find("DATA binary\n").unwrap() + "DATA binary\n".len();
+        assert_eq!(buf.len() - start, 3 * 16);
+    }
+}
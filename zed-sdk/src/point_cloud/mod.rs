@@ -0,0 +1,268 @@
+mod export;
+pub use export::*;
+
+use crate::{ColorOrder, ConfidenceMap, Error, PointCloud, Result, Transform};
+
+/// A point with its position and its color in RGBA order.
+pub type ColoredPoint = (f32, f32, f32, [u8; 4]);
+
+/// Iterates over the valid points of a colored point cloud.
+///
+/// A point is valid if its coordinates are finite. Points can further be
+/// filtered by their distance to the camera and by the depth confidence.
+/// The view borrows the rows of the buffer, so it can be built from a
+/// retrieved [PointCloud] or from plain slices. A [Transform] can be
+/// applied to the points that pass the filters.
+#[derive(Debug, Clone)]
+pub struct PointCloudView<'a> {
+    width: usize,
+    rows: Vec<&'a [[f32; 4]]>,
+    color_order: ColorOrder,
+    range: Option<(f32, f32)>,
+    confidence: Option<(Vec<&'a [f32]>, f32)>,
+    transform: Option<Transform>,
+}
+
+impl<'a> PointCloudView<'a> {
+    /// Creates a view over row-major points `width` points per row.
+    pub fn from_slice(
+        points: &'a [[f32; 4]],
+        width: usize,
+        color_order: ColorOrder,
+    ) -> Result<Self> {
+        let rows = split_rows(points, width)?;
+        Ok(Self::from_rows(width, rows, color_order))
+    }
+
+    fn from_rows(width: usize, rows: Vec<&'a [[f32; 4]]>, color_order: ColorOrder) -> Self {
+        Self {
+            width,
+            rows,
+            color_order,
+            range: None,
+            confidence: None,
+            transform: None,
+        }
+    }
+
+    pub fn resolution(&self) -> (usize, usize) {
+        (self.width, self.rows.len())
+    }
+
+    /// Keeps the points whose distance to the camera is within `min` and
+    /// `max`, inclusive.
+    pub fn range(self, min: f32, max: f32) -> Self {
+        Self {
+            range: Some((min, max)),
+            ..self
+        }
+    }
+
+    /// Keeps the points whose confidence value is at most `threshold`.
+    ///
+    /// The confidence map must have the same resolution as the point
+    /// cloud.
+    pub fn confidence(self, map: &'a ConfidenceMap, threshold: f32) -> Result<Self> {
+        let rows: Vec<_> = map.mat().rows().collect();
+        self.confidence_rows(map.width(), rows, threshold)
+    }
+
+    /// Keeps the points whose confidence value is at most `threshold`,
+    /// where `values` holds the confidence of each point in row-major order.
+    pub fn confidence_slice(self, values: &'a [f32], threshold: f32) -> Result<Self> {
+        let width = self.width;
+        let rows = split_rows(values, width)?;
+        self.confidence_rows(width, rows, threshold)
+    }
+
+    fn confidence_rows(self, width: usize, rows: Vec<&'a [f32]>, threshold: f32) -> Result<Self> {
+        if (width, rows.len()) != self.resolution() {
+            return Err(Error::Desc(
+                format!(
+                    "confidence map of size {}x{} does not match point cloud of size {}x{}",
+                    width,
+                    rows.len(),
+                    self.width,
+                    self.rows.len()
+                )
+                .into(),
+            ));
+        }
+
+        Ok(Self {
+            confidence: Some((rows, threshold)),
+            ..self
+        })
+    }
+
+    /// Transforms the points after they are filtered, e.g. into the world
+    /// frame. The range filter still applies to the camera distance.
+    pub fn transform(self, transform: Transform) -> Self {
+        Self {
+            transform: Some(transform),
+            ..self
+        }
+    }
+
+    /// Iterates over the valid points in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = ColoredPoint> + '_ {
+        self.rows.iter().enumerate().flat_map(move |(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter_map(move |(x, point)| self.point(x, y, point))
+        })
+    }
+
+    fn point(&self, x: usize, y: usize, &[px, py, pz, color]: &[f32; 4]) -> Option<ColoredPoint> {
+        if !(px.is_finite() && py.is_finite() && pz.is_finite()) {
+            return None;
+        }
+
+        if let Some((min, max)) = self.range {
+            let dist = (px * px + py * py + pz * pz).sqrt();
+            if !(min..=max).contains(&dist) {
+                return None;
+            }
+        }
+
+        if let Some((rows, threshold)) = &self.confidence {
+            let value = rows[y][x];
+            if !(value.is_finite() && value <= *threshold) {
+                return None;
+            }
+        }
+
+        let [px, py, pz] = match &self.transform {
+            Some(transform) => transform.apply([px, py, pz]),
+            None => [px, py, pz],
+        };
+        Some((px, py, pz, decode_color(color, self.color_order)))
+    }
+}
+
+impl PointCloud {
+    /// Creates a view over the valid points, or fails if the point cloud
+    /// has no color.
+    pub fn view(&self) -> Result<PointCloudView<'_>> {
+        let color_order = self
+            .color_order()
+            .ok_or(Error::Desc("the point cloud has no color".into()))?;
+        let rows = self.mat().rows().collect();
+        Ok(PointCloudView::from_rows(self.width(), rows, color_order))
+    }
+}
+
+/// Unpacks the color bytes stored in a float into RGBA order.
+pub fn decode_color(packed: f32, order: ColorOrder) -> [u8; 4] {
+    let [c0, c1, c2, c3] = packed.to_ne_bytes();
+
+    match order {
+        ColorOrder::Rgba => [c0, c1, c2, c3],
+        ColorOrder::Bgra => [c2, c1, c0, c3],
+        ColorOrder::Argb => [c1, c2, c3, c0],
+        ColorOrder::Abgr => [c3, c2, c1, c0],
+    }
+}
+
+fn split_rows<T>(values: &[T], width: usize) -> Result<Vec<&[T]>> {
+    if width == 0 || values.len() % width != 0 {
+        return Err(Error::Desc(
+            format!(
+                "buffer of length {} cannot be split into rows of width {}",
+                values.len(),
+                width
+            )
+            .into(),
+        ));
+    }
+    Ok(values.chunks(width).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pack(bytes: [u8; 4]) -> f32 {
+        f32::from_ne_bytes(bytes)
+    }
+
+    #[test]
+    fn decode_rgba_and_bgra() {
+        let packed = pack([1, 2, 3, 4]);
+        assert_eq!(decode_color(packed, ColorOrder::Rgba), [1, 2, 3, 4]);
+        assert_eq!(decode_color(packed, ColorOrder::Bgra), [3, 2, 1, 4]);
+        assert_eq!(decode_color(packed, ColorOrder::Argb), [2, 3, 4, 1]);
+        assert_eq!(decode_color(packed, ColorOrder::Abgr), [4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn iterate_valid_points_only() {
+        let color = pack([10, 20, 30, 255]);
+        let points = [
+            [1.0, 2.0, 3.0, color],
+            [f32::NAN, 0.0, 1.0, color],
+            [0.0, f32::INFINITY, 1.0, color],
+            [4.0, 5.0, f32::NEG_INFINITY, color],
+            [-1.0, 0.5, 2.0, color],
+            [0.0, 0.0, 0.0, color],
+        ];
+        let view = PointCloudView::from_slice(&points, 3, ColorOrder::Bgra).unwrap();
+        assert_eq!(view.resolution(), (3, 2));
+
+        let got: Vec<_> = view.iter().collect();
+        assert_eq!(
+            got,
+            vec![
+                (1.0, 2.0, 3.0, [30, 20, 10, 255]),
+                (-1.0, 0.5, 2.0, [30, 20, 10, 255]),
+                (0.0, 0.0, 0.0, [30, 20, 10, 255]),
+            ]
+        );
+    }
+
+    #[test]
+    fn filter_by_range() {
+        let color = pack([0, 0, 0, 0]);
+        let points = [
+            [0.0, 0.0, 0.5, color],
+            [0.0, 3.0, 4.0, color],
+            [0.0, 0.0, 20.0, color],
+        ];
+        let view = PointCloudView::from_slice(&points, 3, ColorOrder::Rgba)
+            .unwrap()
+            .range(1.0, 5.0);
+
+        let got: Vec<_> = view.iter().map(|(x, y, z, _)| (x, y, z)).collect();
+        assert_eq!(got, vec![(0.0, 3.0, 4.0)]);
+    }
+
+    #[test]
+    fn filter_by_confidence() {
+        let color = pack([0, 0, 0, 0]);
+        let points = [
+            [1.0, 0.0, 0.0, color],
+            [2.0, 0.0, 0.0, color],
+            [3.0, 0.0, 0.0, color],
+            [4.0, 0.0, 0.0, color],
+        ];
+        let confidence = [10.0, 60.0, f32::NAN, 50.0];
+        let view = PointCloudView::from_slice(&points, 2, ColorOrder::Rgba)
+            .unwrap()
+            .confidence_slice(&confidence, 50.0)
+            .unwrap();
+
+        let got: Vec<_> = view.iter().map(|(x, _, _, _)| x).collect();
+        assert_eq!(got, vec![1.0, 4.0]);
+    }
+
+    #[test]
+    fn reject_mismatched_buffers() {
+        let points = [[0.0; 4]; 6];
+        assert!(PointCloudView::from_slice(&points, 4, ColorOrder::Rgba).is_err());
+        assert!(PointCloudView::from_slice(&points, 0, ColorOrder::Rgba).is_err());
+
+        let view = PointCloudView::from_slice(&points, 3, ColorOrder::Rgba).unwrap();
+        assert!(view.clone().confidence_slice(&[0.0; 3], 50.0).is_err());
+        assert!(view.confidence_slice(&[0.0; 6], 50.0).is_ok());
+    }
+}
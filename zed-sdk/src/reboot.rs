@@ -0,0 +1,260 @@
+use crate::{
+    common::*,
+    error::{code_to_result, Error, ErrorContext, Operation},
+    get_device_list, input_source as is, recording_state as rs, streaming_state as ss, Camera,
+    CameraBuilder, CameraState, DeviceInfo, ErrorCode, Result,
+};
+use std::{thread, time::Instant};
+
+/// How long to wait for a rebooting camera to disappear from the device
+/// list before waiting for it to come back.
+const DISAPPEAR_GRACE: Duration = Duration::from_secs(3);
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RebootKind {
+    /// Resets the video module.
+    Soft,
+    /// Resets the sensors and video modules.
+    Full,
+}
+
+/// Performs a hardware reset of the camera with the serial number.
+///
+/// The serial number 0 resets the first camera detected. It is only
+/// supported by ZED 2 and ZED 2i.
+pub fn reboot(sn: c_int, kind: RebootKind) -> Result<()> {
+    let code = unsafe { sys::sl_reboot(sn, kind == RebootKind::Full) };
+    code_to_result(code).map_err(|err| err.with_context(reboot_context(sn)))
+}
+
+impl<'a> CameraBuilder<'a> {
+    /// Reboots the USB camera with the serial number, waits for it to be
+    /// listed as available again, and opens it.
+    ///
+    /// The serial number 0 picks the first camera listed by
+    /// [get_device_list], which must report its serial number to be found
+    /// again after the reboot. Opening is retried on retryable errors until
+    /// the timeout elapses.
+    pub fn reboot_and_open(
+        self,
+        sn: c_int,
+        kind: RebootKind,
+        timeout: Duration,
+    ) -> Result<Camera<is::USB, rs::Inactive, ss::Inactive>> {
+        let deadline = Instant::now() + timeout;
+        let sn = resolve_serial(sn, &get_device_list())?;
+        reboot(sn, kind)?;
+
+        wait_and_open(
+            sn,
+            deadline,
+            get_device_list,
+            || self.clone().open_serial(sn),
+            Instant::now,
+            thread::sleep,
+        )
+    }
+}
+
+/// Replaces the serial number 0 by the serial number of the first listed
+/// camera.
+fn resolve_serial(sn: c_int, devices: &[DeviceInfo]) -> Result<c_int> {
+    if sn != 0 {
+        return Ok(sn);
+    }
+
+    match devices.first() {
+        Some(dev) if dev.serial_number != 0 => Ok(dev.serial_number),
+        Some(_) => Err(
+            Error::Desc("the first camera does not report its serial number".into())
+                .with_context(reboot_context(sn)),
+        ),
+        None => Err(Error::Code(ErrorCode::SL_ERROR_CODE_CAMERA_NOT_DETECTED)
+            .with_context(reboot_context(sn))),
+    }
+}
+
+/// Waits for the rebooted camera to leave the device list, then for it to
+/// be listed as available, and opens it.
+///
+/// The device list, the clock and the sleep are passed in so the polling can
+/// be tested without cameras.
+fn wait_and_open<T, L, O, N, S>(
+    sn: c_int,
+    deadline: Instant,
+    mut list_devices: L,
+    mut open: O,
+    now: N,
+    mut sleep: S,
+) -> Result<T>
+where
+    L: FnMut() -> Vec<DeviceInfo>,
+    O: FnMut() -> Result<T>,
+    N: Fn() -> Instant,
+    S: FnMut(Duration),
+{
+    let mut device_state = || {
+        list_devices()
+            .into_iter()
+            .find(|dev| dev.serial_number == sn)
+            .map(|dev| dev.state)
+    };
+
+    let grace = now() + DISAPPEAR_GRACE;
+    while now() < grace.min(deadline) && device_state().is_some() {
+        sleep(POLL_INTERVAL);
+    }
+
+    loop {
+        if device_state() == Some(CameraState::SL_CAMERA_STATE_AVAILABLE) {
+            match open() {
+                Ok(camera) => return Ok(camera),
+                Err(err) if err.kind().is_retryable() && now() < deadline => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        if now() >= deadline {
+            let err = Error::Code(ErrorCode::SL_ERROR_CODE_CAMERA_NOT_DETECTED);
+            return Err(err.with_context(reboot_context(sn)));
+        }
+        sleep(POLL_INTERVAL);
+    }
+}
+
+fn reboot_context(sn: c_int) -> ErrorContext {
+    ErrorContext {
+        serial_number: Some(sn),
+        ..ErrorContext::new(Operation::Reboot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Model;
+    use std::cell::{Cell, RefCell};
+
+    const SN: c_int = 1234;
+    const AVAILABLE: CameraState = CameraState::SL_CAMERA_STATE_AVAILABLE;
+    const NOT_AVAILABLE: CameraState = CameraState::SL_CAMERA_STATE_NOT_AVAILABLE;
+
+    fn device(serial_number: c_int, state: CameraState) -> DeviceInfo {
+        DeviceInfo {
+            id: 0,
+            serial_number,
+            model: Some(Model::SL_MODEL_ZED2),
+            state,
+        }
+    }
+
+    /// Runs `wait_and_open` on a fake clock advanced by the sleeps, with the
+    /// device list and open results given per poll. Returns the result and
+    /// the elapsed time.
+    fn run(
+        timeout: Duration,
+        lists: Vec<Vec<DeviceInfo>>,
+        opens: Vec<Result<()>>,
+    ) -> (Result<()>, Duration) {
+        let start = Instant::now();
+        let clock = Cell::new(start);
+        let lists = RefCell::new(lists.into_iter());
+        let last = RefCell::new(vec![]);
+        let opens = RefCell::new(opens.into_iter());
+
+        let result = wait_and_open(
+            SN,
+            start + timeout,
+            || {
+                if let Some(list) = lists.borrow_mut().next() {
+                    *last.borrow_mut() = list;
+                }
+                last.borrow().clone()
+            },
+            || opens.borrow_mut().next().unwrap_or(Ok(())),
+            || clock.get(),
+            |duration| clock.set(clock.get() + duration),
+        );
+        (result, clock.get() - start)
+    }
+
+    #[test]
+    fn resolve_first_serial() {
+        let devices = [device(1001, AVAILABLE), device(1002, AVAILABLE)];
+        assert_eq!(resolve_serial(SN, &devices), Ok(SN));
+        assert_eq!(resolve_serial(0, &devices), Ok(1001));
+
+        let err = resolve_serial(0, &[]).unwrap_err();
+        assert_eq!(
+            err.code(),
+            Some(ErrorCode::SL_ERROR_CODE_CAMERA_NOT_DETECTED)
+        );
+        assert!(resolve_serial(0, &[device(0, AVAILABLE)]).is_err());
+    }
+
+    #[test]
+    fn open_after_camera_comes_back() {
+        let lists = vec![
+            vec![device(SN, AVAILABLE)],
+            vec![],
+            vec![],
+            vec![device(SN, NOT_AVAILABLE)],
+            vec![device(SN, AVAILABLE)],
+        ];
+        let (result, elapsed) = run(Duration::from_secs(10), lists, vec![]);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(elapsed, POLL_INTERVAL * 3);
+    }
+
+    #[test]
+    fn retry_open_on_retryable_errors() {
+        let lists = vec![vec![], vec![device(SN, AVAILABLE)]];
+        let opens = vec![
+            Err(Error::Code(ErrorCode::SL_ERROR_CODE_CAMERA_DETECTION_ISSUE)),
+            Ok(()),
+        ];
+        let (result, elapsed) = run(Duration::from_secs(10), lists, opens);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(elapsed, POLL_INTERVAL);
+    }
+
+    #[test]
+    fn fail_on_errors_not_retryable() {
+        let err = Error::Code(ErrorCode::SL_ERROR_CODE_INVALID_FIRMWARE);
+        let lists = vec![vec![], vec![device(SN, AVAILABLE)]];
+        let (result, _) = run(Duration::from_secs(10), lists, vec![Err(err.clone())]);
+
+        assert_eq!(result, Err(err));
+    }
+
+    #[test]
+    fn give_up_at_deadline() {
+        let timeout = Duration::from_secs(2);
+        let (result, elapsed) = run(timeout, vec![vec![device(SN, NOT_AVAILABLE)]], vec![]);
+
+        let err = result.unwrap_err();
+        assert_eq!(
+            err.code(),
+            Some(ErrorCode::SL_ERROR_CODE_CAMERA_NOT_DETECTED)
+        );
+        assert_eq!(err.context().unwrap().serial_number, Some(SN));
+        assert_eq!(elapsed, timeout);
+    }
+
+    #[test]
+    fn stop_waiting_for_removal_after_grace() {
+        // A camera that never leaves the list is opened once the grace
+        // period ends.
+        let (result, elapsed) = run(
+            Duration::from_secs(10),
+            vec![vec![device(SN, AVAILABLE)]],
+            vec![],
+        );
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(elapsed, DISAPPEAR_GRACE);
+    }
+}
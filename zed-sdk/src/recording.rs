@@ -1,5 +1,8 @@
 use crate::common::*;
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct RecordingParams {
     pub compression_mode: sys::SL_SVO_COMPRESSION_MODE,
     pub bitrate: u32,
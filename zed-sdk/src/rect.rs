@@ -0,0 +1,120 @@
+use crate::{common::*, Error, Result};
+
+/// A rectangle in pixel coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Checks if the rectangle lies within an image of the size.
+    pub fn is_within(&self, (width, height): (usize, usize)) -> bool {
+        let right = self.x.checked_add(self.width);
+        let bottom = self.y.checked_add(self.height);
+        matches!((right, bottom), (Some(right), Some(bottom)) if right <= width && bottom <= height)
+    }
+
+    pub fn to_raw(&self) -> Result<sys::SL_Rect> {
+        let to_c_int = |value: usize| {
+            c_int::try_from(value)
+                .map_err(|_| Error::Desc(format!("{:?} does not fit in C integers", self).into()))
+        };
+
+        Ok(sys::SL_Rect {
+            x: to_c_int(self.x)?,
+            y: to_c_int(self.y)?,
+            width: to_c_int(self.width)?,
+            height: to_c_int(self.height)?,
+        })
+    }
+
+    pub fn from_raw(rect: &sys::SL_Rect) -> Result<Self> {
+        let sys::SL_Rect {
+            x,
+            y,
+            width,
+            height,
+        } = *rect;
+        let to_usize = |value: c_int| {
+            usize::try_from(value).map_err(|_| {
+                Error::Desc(
+                    format!(
+                        "invalid rectangle x={} y={} width={} height={}",
+                        x, y, width, height
+                    )
+                    .into(),
+                )
+            })
+        };
+
+        Ok(Self {
+            x: to_usize(x)?,
+            y: to_usize(y)?,
+            width: to_usize(width)?,
+            height: to_usize(height)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_within_image() {
+        let size = (1280, 720);
+        assert!(Rect::new(0, 0, 1280, 720).is_within(size));
+        assert!(Rect::new(1000, 700, 280, 20).is_within(size));
+        assert!(Rect::new(1280, 720, 0, 0).is_within(size));
+        assert!(!Rect::new(1000, 700, 281, 20).is_within(size));
+        assert!(!Rect::new(1000, 700, 280, 21).is_within(size));
+        assert!(!Rect::new(1281, 0, 0, 0).is_within(size));
+    }
+
+    #[test]
+    fn reject_overflowing_rect() {
+        let size = (usize::MAX, usize::MAX);
+        assert!(Rect::new(usize::MAX, 0, 0, 1).is_within(size));
+        assert!(!Rect::new(usize::MAX, 0, 1, 1).is_within(size));
+        assert!(!Rect::new(0, 2, 1, usize::MAX - 1).is_within(size));
+    }
+
+    #[test]
+    fn convert_to_and_from_raw() {
+        let rect = Rect::new(10, 20, 300, 400);
+        let raw = rect.to_raw().unwrap();
+        assert_eq!((raw.x, raw.y, raw.width, raw.height), (10, 20, 300, 400));
+        assert_eq!(Rect::from_raw(&raw), Ok(rect));
+
+        let max = c_int::MAX as usize;
+        assert!(Rect::new(max, 0, 1, 1).to_raw().is_ok());
+        assert!(Rect::new(max + 1, 0, 1, 1).to_raw().is_err());
+        assert!(Rect::new(0, 0, 1, max + 1).to_raw().is_err());
+        assert!(Rect::new(0, 0, usize::MAX, 1).to_raw().is_err());
+
+        let negative = sys::SL_Rect {
+            x: -1,
+            y: 0,
+            width: 1,
+            height: 1,
+        };
+        assert!(Rect::from_raw(&negative).is_err());
+    }
+}
@@ -0,0 +1,182 @@
+use crate::{common::*, ensure, Result, RuntimeParameters, SensingMode};
+
+/// The reference frame of 3D measures and poses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING_SNAKE_CASE"))]
+pub enum ReferenceFrame {
+    World,
+    Camera,
+}
+
+impl ReferenceFrame {
+    pub fn to_raw(&self) -> sys::SL_REFERENCE_FRAME {
+        match self {
+            ReferenceFrame::World => sys::SL_REFERENCE_FRAME_SL_REFERENCE_FRAME_WORLD,
+            ReferenceFrame::Camera => sys::SL_REFERENCE_FRAME_SL_REFERENCE_FRAME_CAMERA,
+        }
+    }
+}
+
+/// Parameters that define the behavior of [grab](crate::Camera::grab).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RuntimeParams {
+    sensing_mode: SensingMode,
+    reference_frame: ReferenceFrame,
+    enable_depth: bool,
+    confidence_threshold: u8,
+    texture_confidence_threshold: u8,
+}
+
+impl RuntimeParams {
+    pub fn new() -> Self {
+        Self {
+            sensing_mode: SensingMode::SL_SENSING_MODE_STANDARD,
+            reference_frame: ReferenceFrame::Camera,
+            enable_depth: true,
+            confidence_threshold: 100,
+            texture_confidence_threshold: 100,
+        }
+    }
+
+    pub fn sensing_mode(self, sensing_mode: SensingMode) -> Self {
+        Self {
+            sensing_mode,
+            ..self
+        }
+    }
+
+    pub fn reference_frame(self, reference_frame: ReferenceFrame) -> Self {
+        Self {
+            reference_frame,
+            ..self
+        }
+    }
+
+    pub fn enable_depth(self, yes: bool) -> Self {
+        Self {
+            enable_depth: yes,
+            ..self
+        }
+    }
+
+    /// Sets the threshold within 1 and 100 to reject depth values by
+    /// confidence. The value 100 rejects nothing.
+    pub fn confidence_threshold(self, threshold: u8) -> Self {
+        Self {
+            confidence_threshold: threshold,
+            ..self
+        }
+    }
+
+    /// Sets the threshold within 1 and 100 to reject depth values by
+    /// texture confidence. The value 100 rejects nothing.
+    pub fn texture_confidence_threshold(self, threshold: u8) -> Self {
+        Self {
+            texture_confidence_threshold: threshold,
+            ..self
+        }
+    }
+
+    /// Validates the parameters and converts them to the C struct.
+    pub fn to_raw(&self) -> Result<RuntimeParameters> {
+        let Self {
+            sensing_mode,
+            reference_frame,
+            enable_depth,
+            confidence_threshold,
+            texture_confidence_threshold,
+        } = *self;
+
+        ensure!(
+            (1..=100).contains(&confidence_threshold),
+            "confidence threshold must be within 1 and 100, but get {}",
+            confidence_threshold
+        );
+        ensure!(
+            (1..=100).contains(&texture_confidence_threshold),
+            "texture confidence threshold must be within 1 and 100, but get {}",
+            texture_confidence_threshold
+        );
+
+        Ok(RuntimeParameters {
+            sensing_mode,
+            reference_frame: reference_frame.to_raw(),
+            enable_depth,
+            confidence_threshold: confidence_threshold as c_int,
+            texture_confidence_threshold: texture_confidence_threshold as c_int,
+        })
+    }
+}
+
+impl Default for RuntimeParams {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_match_sdk() {
+        let raw = RuntimeParams::default().to_raw().unwrap();
+        let sdk = RuntimeParameters::default();
+
+        assert_eq!(raw.sensing_mode, sdk.sensing_mode);
+        assert_eq!(raw.reference_frame, sdk.reference_frame);
+        assert_eq!(raw.enable_depth, sdk.enable_depth);
+        assert_eq!(raw.confidence_threshold, sdk.confidence_threshold);
+        assert_eq!(
+            raw.texture_confidence_threshold,
+            sdk.texture_confidence_threshold
+        );
+    }
+
+    #[test]
+    fn convert_custom_params() {
+        let raw = RuntimeParams::new()
+            .sensing_mode(SensingMode::SL_SENSING_MODE_FILL)
+            .reference_frame(ReferenceFrame::World)
+            .enable_depth(false)
+            .confidence_threshold(1)
+            .texture_confidence_threshold(50)
+            .to_raw()
+            .unwrap();
+
+        assert_eq!(raw.sensing_mode, SensingMode::SL_SENSING_MODE_FILL);
+        assert_eq!(
+            raw.reference_frame,
+            sys::SL_REFERENCE_FRAME_SL_REFERENCE_FRAME_WORLD
+        );
+        assert!(!raw.enable_depth);
+        assert_eq!(raw.confidence_threshold, 1);
+        assert_eq!(raw.texture_confidence_threshold, 50);
+    }
+
+    #[test]
+    fn reject_thresholds_out_of_range() {
+        for threshold in [0, 101] {
+            let err = RuntimeParams::new()
+                .confidence_threshold(threshold)
+                .to_raw()
+                .unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!(
+                    "confidence threshold must be within 1 and 100, but get {}",
+                    threshold
+                )
+            );
+
+            let err = RuntimeParams::new()
+                .texture_confidence_threshold(threshold)
+                .to_raw()
+                .unwrap_err();
+            assert!(err.to_string().starts_with("texture confidence threshold"));
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use crate::StreamingCodec;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct StreamingParameters {
     pub codec: StreamingCodec,
     pub bitrate: usize,
@@ -0,0 +1,151 @@
+use crate::{CoordinateSystem, Error, Quaternion, Result, Vector3};
+
+/// A rigid transform, rotating then translating a point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    /// The row-major rotation matrix.
+    pub rotation: [[f32; 3]; 3],
+    pub translation: [f32; 3],
+}
+
+impl Transform {
+    pub const IDENTITY: Self = Self {
+        rotation: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        translation: [0.0, 0.0, 0.0],
+    };
+
+    /// Creates the transform from a rotation quaternion, normalized first,
+    /// and a translation.
+    pub fn from_quaternion(rotation: Quaternion, translation: Vector3) -> Self {
+        let Quaternion { x, y, z, w } = rotation;
+        let norm = (x * x + y * y + z * z + w * w).sqrt();
+        let (x, y, z, w) = (x / norm, y / norm, z / norm, w / norm);
+
+        Self {
+            rotation: [
+                [
+                    1.0 - 2.0 * (y * y + z * z),
+                    2.0 * (x * y - z * w),
+                    2.0 * (x * z + y * w),
+                ],
+                [
+                    2.0 * (x * y + z * w),
+                    1.0 - 2.0 * (x * x + z * z),
+                    2.0 * (y * z - x * w),
+                ],
+                [
+                    2.0 * (x * z - y * w),
+                    2.0 * (y * z + x * w),
+                    1.0 - 2.0 * (x * x + y * y),
+                ],
+            ],
+            translation: [translation.x, translation.y, translation.z],
+        }
+    }
+
+    pub fn apply(&self, point: [f32; 3]) -> [f32; 3] {
+        let [r0, r1, r2] = &self.rotation;
+        let [tx, ty, tz] = self.translation;
+        let dot = |row: &[f32; 3]| row[0] * point[0] + row[1] * point[1] + row[2] * point[2];
+        [dot(r0) + tx, dot(r1) + ty, dot(r2) + tz]
+    }
+
+    pub fn inverse(&self) -> Self {
+        let r = &self.rotation;
+        let rotation = [
+            [r[0][0], r[1][0], r[2][0]],
+            [r[0][1], r[1][1], r[2][1]],
+            [r[0][2], r[1][2], r[2][2]],
+        ];
+        let [tx, ty, tz] = Self {
+            rotation,
+            translation: [0.0; 3],
+        }
+        .apply(self.translation);
+
+        Self {
+            rotation,
+            translation: [-tx, -ty, -tz],
+        }
+    }
+
+    /// Creates the transform applying `other` first, then `self`.
+    pub fn compose(&self, other: &Transform) -> Self {
+        let rotation = [0, 1, 2].map(|i| {
+            [0, 1, 2].map(|j| {
+                (0..3)
+                    .map(|k| self.rotation[i][k] * other.rotation[k][j])
+                    .sum()
+            })
+        });
+
+        Self {
+            rotation,
+            translation: self.apply(other.translation),
+        }
+    }
+
+    /// Expresses the transform, given in the coordinate system, in the image
+    /// coordinate system, i.e. x right, y down and z forward.
+    pub fn to_image_frame(&self, system: CoordinateSystem) -> Result<Self> {
+        let basis = Self {
+            rotation: image_basis(system)?,
+            translation: [0.0; 3],
+        };
+        Ok(basis.compose(self).compose(&basis.inverse()))
+    }
+
+    /// Gets the row-major homogeneous 4x4 matrix.
+    pub fn to_matrix(&self) -> [[f32; 4]; 4] {
+        let [r0, r1, r2] = self.rotation;
+        let [tx, ty, tz] = self.translation;
+        [
+            [r0[0], r0[1], r0[2], tx],
+            [r1[0], r1[1], r1[2], ty],
+            [r2[0], r2[1], r2[2], tz],
+            [0.0, 0.0, 0.0, 1.0],
+        ]
+    }
+}
+
+/// Gets the matrix mapping coordinates in the system to the image
+/// coordinate system. It is a reflection for left-handed systems.
+fn image_basis(system: CoordinateSystem) -> Result<[[f32; 3]; 3]> {
+    use CoordinateSystem as C;
+
+    let basis = match system {
+        C::SL_COORDINATE_SYSTEM_IMAGE => Transform::IDENTITY.rotation,
+        // x right, y up, z forward
+        C::SL_COORDINATE_SYSTEM_LEFT_HANDED_Y_UP => {
+            [[1.0, 0.0, 0.0], [0.0, -1.0, 0.0], [0.0, 0.0, 1.0]]
+        }
+        // x right, y up, z backward
+        C::SL_COORDINATE_SYSTEM_RIGHT_HANDED_Y_UP => {
+            [[1.0, 0.0, 0.0], [0.0, -1.0, 0.0], [0.0, 0.0, -1.0]]
+        }
+        // x right, y forward, z up
+        C::SL_COORDINATE_SYSTEM_RIGHT_HANDED_Z_UP => {
+            [[1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]]
+        }
+        // x forward, y right, z up
+        C::SL_COORDINATE_SYSTEM_LEFT_HANDED_Z_UP => {
+            [[0.0, 1.0, 0.0], [0.0, 0.0, -1.0], [1.0, 0.0, 0.0]]
+        }
+        // x forward, y left, z up
+        C::SL_COORDINATE_SYSTEM_RIGHT_HANDED_Z_UP_X_FWD => {
+            [[0.0, -1.0, 0.0], [0.0, 0.0, -1.0], [1.0, 0.0, 0.0]]
+        }
+        system => {
+            return Err(Error::Desc(
+                format!("unsupported coordinate system {:?}", system).into(),
+            ))
+        }
+    };
+    Ok(basis)
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}